@@ -0,0 +1,126 @@
+/*
+ * Copyright 2016 rs-transducers developers
+ *
+ * Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+ * http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+ * <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+ * option. This file may not be copied, modified, or distributed
+ * except according to those terms.
+ */
+
+// Compares the `Vec` and iterator applications against the equivalent
+// `Iterator::map/filter` chain.  Run with `cargo bench`.
+//
+// Each case is its own function, so that it is compiled the same way
+// wherever it is called from, and the fastest of `RUNS` runs is reported, as
+// the slower runs mostly measure the machine rather than the code.  Every case
+// takes the same `&Vec`, as `Ref` is only implemented for `Vec`.
+
+#![allow(clippy::ptr_arg)]
+
+extern crate rs_transducers;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rs_transducers::TransducerExt;
+use rs_transducers::transducers;
+use rs_transducers::applications::vec::{Into, Ref};
+use rs_transducers::applications::iter::TransduceIter;
+
+const SIZE: usize = 1_000_000;
+const RUNS: usize = 101;
+
+/// Times `iterator` and `transducer` alternately, so that both see the same
+/// conditions, and prints the fastest run of each.
+fn compare<S>(name: &str, source: &S, iterator: fn(&S) -> usize, transducer: fn(&S) -> usize) {
+    assert_eq!(iterator(source), transducer(source));
+    let mut fastest = (Duration::MAX, Duration::MAX);
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        black_box(iterator(black_box(source)));
+        fastest.0 = fastest.0.min(start.elapsed());
+
+        let start = Instant::now();
+        black_box(transducer(black_box(source)));
+        fastest.1 = fastest.1.min(start.elapsed());
+    }
+    println!("{:<12} iterator {:>8.3} ms, transducer {:>8.3} ms",
+             name,
+             fastest.0.as_secs_f64() * 1000.0,
+             fastest.1.as_secs_f64() * 1000.0);
+}
+
+#[inline(never)]
+fn vec_iterator(source: &Vec<usize>) -> usize {
+    let result:Vec<usize> = source.clone().into_iter()
+        .map(|x| x * 3)
+        .filter(|x| x & 1 == 0)
+        .collect();
+    result.len()
+}
+
+#[inline(never)]
+fn vec_transduce_into(source: &Vec<usize>) -> usize {
+    let transducer = transducers::map(|x: usize| x * 3).filter(|x: &usize| x & 1 == 0);
+    source.clone().transduce_into(transducer).unwrap().len()
+}
+
+#[inline(never)]
+fn vec_ref_iterator(source: &Vec<usize>) -> usize {
+    let result:Vec<usize> = source.iter()
+        .map(|x| x * 3)
+        .filter(|x| x & 1 == 0)
+        .collect();
+    result.len()
+}
+
+#[inline(never)]
+fn vec_ref_transduce_ref(source: &Vec<usize>) -> usize {
+    let transducer = transducers::map(|x: &usize| x * 3).filter(|x: &usize| x & 1 == 0);
+    source.transduce_ref(transducer).unwrap().len()
+}
+
+#[inline(never)]
+fn iter_iterator(source: &Vec<usize>) -> usize {
+    source.iter()
+        .map(|x| x * 3)
+        .filter(|x| x & 1 == 0)
+        .fold(0, |acc, x| acc ^ x)
+}
+
+#[inline(never)]
+fn iter_transduce(source: &Vec<usize>) -> usize {
+    let transducer = transducers::map(|x: &usize| x * 3).filter(|x: &usize| x & 1 == 0);
+    source.iter()
+        .transduce(transducer)
+        .fold(0, |acc, x| acc ^ x)
+}
+
+#[inline(never)]
+fn iter_next_iterator(source: &Vec<usize>) -> usize {
+    let mut acc = 0;
+    for x in source.iter().map(|x| x * 3).filter(|x| x & 1 == 0) {
+        acc ^= x;
+    }
+    acc
+}
+
+#[inline(never)]
+fn iter_next_transduce(source: &Vec<usize>) -> usize {
+    let transducer = transducers::map(|x: &usize| x * 3).filter(|x: &usize| x & 1 == 0);
+    let mut acc = 0;
+    for x in source.iter().transduce(transducer) {
+        acc ^= x;
+    }
+    acc
+}
+
+fn main() {
+    let source:Vec<usize> = (0..SIZE).collect();
+
+    compare("vec", &source, vec_iterator, vec_transduce_into);
+    compare("vec ref", &source, vec_ref_iterator, vec_ref_transduce_ref);
+    compare("iter fold", &source, iter_iterator, iter_transduce);
+    compare("iter next", &source, iter_next_iterator, iter_next_transduce);
+}
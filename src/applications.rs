@@ -9,15 +9,21 @@
  */
 
 pub mod vec {
-    use std::cell::RefCell;
-    use std::rc::Rc;
+    use std::mem;
 
-    use ::{Transducer, Reducing, StepResult};
+    use ::{Transducer, Reducing, StepResult, transduce};
 
     pub trait Ref {
         type Input;
 
-        fn transduce_ref<'a, T, O, RO, E>(&'a self, transducer: T) -> Result<Vec<O>, E>
+        #[allow(clippy::result_unit_err)]
+        fn transduce_ref<'a, T, O, RO>(&'a self, transducer: T) -> Result<Vec<O>, ()>
+            where RO: Reducing<&'a Self::Input, Vec<O>, ()>,
+                  T: Transducer<VecReducer<O>, RO=RO>;
+
+        /// As `transduce_ref`, but for reducing functions that can fail, such
+        /// as those of `try_map`.
+        fn try_transduce_ref<'a, T, O, RO, E>(&'a self, transducer: T) -> Result<Vec<O>, E>
             where RO: Reducing<&'a Self::Input, Vec<O>, E>,
                   T: Transducer<VecReducer<O>, RO=RO>;
     }
@@ -25,86 +31,143 @@ pub mod vec {
     pub trait Into {
         type Input;
 
-        fn transduce_into<T, O, RO, E>(self, transducer: T) -> Result<Vec<O>, E>
+        #[allow(clippy::result_unit_err)]
+        fn transduce_into<T, O, RO>(self, transducer: T) -> Result<Vec<O>, ()>
+            where RO: Reducing<Self::Input, Vec<O>, ()>,
+                  T: Transducer<VecReducer<O>, RO=RO>;
+
+        /// As `transduce_into`, but for reducing functions that can fail,
+        /// such as those of `try_map`.
+        fn try_transduce_into<T, O, RO, E>(self, transducer: T) -> Result<Vec<O>, E>
             where RO: Reducing<Self::Input, Vec<O>, E>,
                   T: Transducer<VecReducer<O>, RO=RO>;
     }
 
-    pub struct VecReducer<O>(Rc<RefCell<Vec<O>>>);
+    pub struct VecReducer<O>(Vec<O>);
 
-    impl<'a, O> Reducing<O, Vec<O>, ()> for VecReducer<O> {
+    /// The vector grows as values arrive rather than being sized from the
+    /// source, as transducers such as `filter` often produce far fewer values.
+    impl<O> Default for VecReducer<O> {
+        fn default() -> Self {
+            VecReducer(Vec::new())
+        }
+    }
+
+    impl<O, E> Reducing<O, Vec<O>, E> for VecReducer<O> {
         type Item = O;
 
         #[inline]
-        fn step(&mut self, value: O) -> Result<StepResult, ()> {
-            self.0.borrow_mut().push(value);
+        fn step(&mut self, value: O) -> Result<StepResult, E> {
+            self.0.push(value);
             Ok(StepResult::Continue)
         }
 
-        fn complete(&mut self) -> Result<(), ()> {
-            Ok(())
+        fn complete(&mut self) -> Result<Vec<O>, E> {
+            Ok(mem::take(&mut self.0))
         }
     }
 
     impl<X> Ref for Vec<X> {
         type Input = X;
 
-        fn transduce_ref<'a, T, O, RO, E>(&'a self, transducer: T) -> Result<Vec<O>, E>
+        fn transduce_ref<'a, T, O, RO>(&'a self, transducer: T) -> Result<Vec<O>, ()>
+            where RO: Reducing<&'a Self::Input, Vec<O>, ()>,
+                  T: Transducer<VecReducer<O>, RO=RO> {
+            self.try_transduce_ref(transducer)
+        }
+
+        fn try_transduce_ref<'a, T, O, RO, E>(&'a self, transducer: T) -> Result<Vec<O>, E>
             where RO: Reducing<&'a Self::Input, Vec<O>, E>,
                   T: Transducer<VecReducer<O>, RO=RO> {
-            let res = Rc::new(RefCell::new(Vec::with_capacity(self.len())));
-            {
-                let rr = VecReducer(res.clone());
-                let mut reducing = transducer.new(rr);
-                reducing.init();
-                for val in self.iter() {
-                    match reducing.step(val) {
-                        Ok(StepResult::Continue) => (),
-                        Ok(StepResult::Stop) => break,
-                        Err(e) => return Err(e)
-                    }
-                }
-                try!(reducing.complete())
-            }
-            Ok(match Rc::try_unwrap(res) {
-                Ok(res) => res.into_inner(),
-                Err(_) => panic!("Other refs")
-            })
+            transduce(transducer, VecReducer::default(), self.iter())
         }
     }
 
     impl<X> Into for Vec<X> {
         type Input = X;
 
-        fn transduce_into<T, O, RO, E>(self, transducer: T) -> Result<Vec<O>, E>
+        fn transduce_into<T, O, RO>(self, transducer: T) -> Result<Vec<O>, ()>
+            where RO: Reducing<Self::Input, Vec<O>, ()>,
+                  T: Transducer<VecReducer<O>, RO=RO> {
+            self.try_transduce_into(transducer)
+        }
+
+        fn try_transduce_into<T, O, RO, E>(self, transducer: T) -> Result<Vec<O>, E>
             where RO: Reducing<Self::Input, Vec<O>, E>,
                   T: Transducer<VecReducer<O>, RO=RO> {
-            let res = Rc::new(RefCell::new(Vec::with_capacity(self.len())));
-            {
-                let rr = VecReducer(res.clone());
-                let mut reducing = transducer.new(rr);
-                reducing.init();
-                for val in self.into_iter() {
-                    match reducing.step(val) {
-                        Ok(StepResult::Continue) => (),
-                        Ok(StepResult::Stop) => break,
-                        Err(e) => return Err(e)
-                    }
-                }
-                try!(reducing.complete())
-            }
-            Ok(match Rc::try_unwrap(res) {
-                Ok(res) => res.into_inner(),
-                Err(_) => panic!("Other refs")
-            })
+            transduce(transducer, VecReducer::default(), self)
+        }
+    }
+}
+
+pub mod collect {
+    use std::mem;
+
+    use ::{Transducer, Reducing, StepResult, transduce};
+
+    /// Transduce any source into any collection that can be extended, for
+    /// example a `HashMap`, `BTreeSet` or `String`.
+    pub trait Collect {
+        type Input;
+
+        #[allow(clippy::result_unit_err)]
+        fn transduce_collect<C, T, RO>(self, transducer: T) -> Result<C, ()>
+            where C: Default,
+                  RO: Reducing<Self::Input, C, ()>,
+                  T: Transducer<ExtendReducer<C>, RO=RO>;
+
+        /// As `transduce_collect`, but for reducing functions that can fail,
+        /// such as those of `try_map`.
+        fn try_transduce_collect<C, T, RO, E>(self, transducer: T) -> Result<C, E>
+            where C: Default,
+                  RO: Reducing<Self::Input, C, E>,
+                  T: Transducer<ExtendReducer<C>, RO=RO>;
+    }
+
+    pub struct ExtendReducer<C>(C);
+
+    impl<C, O, E> Reducing<O, C, E> for ExtendReducer<C>
+        where C: Extend<O> + Default {
+
+        type Item = O;
+
+        #[inline]
+        fn step(&mut self, value: O) -> Result<StepResult, E> {
+            self.0.extend(Some(value));
+            Ok(StepResult::Continue)
+        }
+
+        fn complete(&mut self) -> Result<C, E> {
+            Ok(mem::take(&mut self.0))
+        }
+    }
+
+    impl<S> Collect for S
+        where S: IntoIterator {
+
+        type Input = S::Item;
+
+        fn transduce_collect<C, T, RO>(self, transducer: T) -> Result<C, ()>
+            where C: Default,
+                  RO: Reducing<Self::Input, C, ()>,
+                  T: Transducer<ExtendReducer<C>, RO=RO> {
+            self.try_transduce_collect(transducer)
+        }
+
+        fn try_transduce_collect<C, T, RO, E>(self, transducer: T) -> Result<C, E>
+            where C: Default,
+                  RO: Reducing<Self::Input, C, E>,
+                  T: Transducer<ExtendReducer<C>, RO=RO> {
+            transduce(transducer, ExtendReducer(C::default()), self)
         }
     }
 }
 
 pub mod iter {
-    use std::cell::RefCell;
     use std::collections::VecDeque;
-    use std::rc::Rc;
+    use std::marker::PhantomData;
+    use std::mem;
+    use std::ops::ControlFlow;
 
     use ::{Transducer, Reducing, StepResult};
 
@@ -112,8 +175,14 @@ pub mod iter {
         type UnderlyingIterator;
         type Item;
 
-        fn transduce<T, O, RO, E>(self, transducer: T) -> TransduceIterator<Self::UnderlyingIterator, O, RO>
-            where RO: Reducing<Self::Item, (), E>,
+        fn transduce<T, O, RO>(self, transducer: T) -> TransduceIterator<Self::UnderlyingIterator, O, RO>
+            where RO: Reducing<Self::Item, IterBuffer<O>, ()>,
+                  T: Transducer<IterReducer<O>, RO=RO>;
+
+        /// As `transduce`, but for reducing functions that can fail.  The
+        /// resulting iterator yields `Result`s, and ends after the first error.
+        fn try_transduce<T, O, RO, E>(self, transducer: T) -> TryTransduceIterator<Self::UnderlyingIterator, O, RO, E>
+            where RO: Reducing<Self::Item, IterBuffer<O>, E>,
                   T: Transducer<IterReducer<O>, RO=RO>;
     }
 
@@ -123,93 +192,296 @@ pub mod iter {
         type UnderlyingIterator = I;
         type Item = T;
 
-        fn transduce<TR, O, RO, E>(self, transducer: TR) -> TransduceIterator<Self::UnderlyingIterator, O, RO>
-            where RO: Reducing<Self::Item, (), E>,
+        fn transduce<TR, O, RO>(self, transducer: TR) -> TransduceIterator<Self::UnderlyingIterator, O, RO>
+            where RO: Reducing<Self::Item, IterBuffer<O>, ()>,
                   TR: Transducer<IterReducer<O>, RO=RO> {
-            let buffer = Rc::new(RefCell::new(VecDeque::new()));
+            TransduceIterator(self.try_transduce(transducer))
+        }
 
-            TransduceIterator {
+        fn try_transduce<TR, O, RO, E>(self, transducer: TR) -> TryTransduceIterator<Self::UnderlyingIterator, O, RO, E>
+            where RO: Reducing<Self::Item, IterBuffer<O>, E>,
+                  TR: Transducer<IterReducer<O>, RO=RO> {
+            TryTransduceIterator {
                 underlying: self,
-                buffer: buffer.clone(),
-                rf: transducer.new(IterReducer(buffer.clone())),
-                runoff: false
+                rf: transducer.new(IterReducer(IterBuffer::default())),
+                finished: false,
+                error: None,
+                o: PhantomData
+            }
+        }
+    }
+
+    /// Unlike the other applications the values cannot wait for `complete`,
+    /// as an iterator must be able to return them after each step.  So the
+    /// iterator takes them from `accumulated` as they arrive, and `complete`
+    /// hands back any that remain.
+    pub struct IterReducer<T>(IterBuffer<T>);
+
+    /// The values produced by the reducing function and not yet returned by
+    /// the iterator.  Most steps produce at most one value, so the oldest is
+    /// held apart from the queue.
+    pub struct IterBuffer<T> {
+        next: Option<T>,
+        queue: VecDeque<T>
+    }
+
+    impl<T> Default for IterBuffer<T> {
+        fn default() -> Self {
+            IterBuffer {
+                next: None,
+                queue: VecDeque::new()
             }
         }
     }
 
-    pub struct IterReducer<T>(Rc<RefCell<VecDeque<T>>>);
+    impl<T> IterBuffer<T> {
+        #[inline]
+        fn push(&mut self, value: T) {
+            if self.next.is_none() {
+                self.next = Some(value);
+            } else {
+                self.queue.push_back(value);
+            }
+        }
+
+        #[inline]
+        fn pop(&mut self) -> Option<T> {
+            let value = self.next.take();
+            if value.is_some() {
+                self.next = self.queue.pop_front();
+            }
+            value
+        }
+    }
 
-    impl<T> Reducing<T, (), ()> for IterReducer<T> {
+    impl<T, E> Reducing<T, IterBuffer<T>, E> for IterReducer<T> {
         type Item = T;
 
         #[inline]
-        fn step(&mut self, value: T) -> Result<StepResult, ()> {
-            self.0.borrow_mut().push_back(value);
+        fn step(&mut self, value: T) -> Result<StepResult, E> {
+            self.0.push(value);
             Ok(StepResult::Continue)
         }
 
-        fn complete(&mut self) -> Result<(), ()> {
-            Ok(())
+        #[inline]
+        fn accumulated(&mut self) -> Option<&mut IterBuffer<T>> {
+            Some(&mut self.0)
+        }
+
+        fn complete(&mut self) -> Result<IterBuffer<T>, E> {
+            Ok(mem::take(&mut self.0))
+        }
+    }
+
+    pub struct TransduceIterator<I, O, RF>(TryTransduceIterator<I, O, RF, ()>);
+
+    impl<I, IN, O, RF> Iterator for TransduceIterator<I, O, RF>
+        where I: Iterator<Item=IN>,
+              RF: Reducing<IN, IterBuffer<O>, ()> {
+
+        type Item = O;
+
+        #[inline]
+        fn next(&mut self) -> Option<Self::Item> {
+            self.0.next().map(|res| res.expect("Reducing function failed, use try_transduce instead"))
+        }
+
+        #[inline]
+        fn fold<B, F>(self, init: B, mut f: F) -> B
+            where F: FnMut(B, Self::Item) -> B {
+            self.0.fold(init, |acc, res| f(acc, res.expect("Reducing function failed, use try_transduce instead")))
         }
     }
 
-    pub struct TransduceIterator<I, O, RF> {
+    pub struct TryTransduceIterator<I, O, RF, E> {
         underlying: I,
-        buffer: Rc<RefCell<VecDeque<O>>>,
         rf: RF,
-        runoff: bool
+        finished: bool,
+        error: Option<E>,
+        o: PhantomData<O>
     }
 
-    impl<I, IN, O, RF> Iterator for TransduceIterator<I, O, RF>
+    #[inline]
+    fn accumulated<IN, O, RF, E>(rf: &mut RF) -> &mut IterBuffer<O>
+        where RF: Reducing<IN, IterBuffer<O>, E> {
+        rf.accumulated().expect("Reducing function does not return the underlying accumulated")
+    }
+
+    /// Completes the reducing function, unless a step failed, leaving the
+    /// values it hands back to be taken from `accumulated`.  Returns the error
+    /// to end with.
+    fn finish<IN, O, RF, E>(rf: &mut RF, result: Result<(), E>) -> Option<E>
+        where RF: Reducing<IN, IterBuffer<O>, E> {
+        match result.and_then(|()| rf.complete()) {
+            Ok(runoff) => {
+                *accumulated(rf) = runoff;
+                None
+            },
+            Err(e) => Some(e)
+        }
+    }
+
+    impl<I, IN, O, RF, E> Iterator for TryTransduceIterator<I, O, RF, E>
         where I: Iterator<Item=IN>,
-              RF: Reducing<IN, (), ()> {
+              RF: Reducing<IN, IterBuffer<O>, E> {
 
-        type Item = O;
+        type Item = Result<O, E>;
 
         #[inline]
         fn next(&mut self) -> Option<Self::Item> {
             loop {
-                if !self.runoff && self.buffer.borrow().is_empty() {
-                    match self.underlying.next() {
-                        None => {
-                            self.runoff = true;
-                            self.rf.complete().unwrap();
-                        },
-                        Some(value) => {
-                            match self.rf.step(value) {
-                                Ok(StepResult::Continue) => (),
-                                Ok(StepResult::Stop) => {
-                                    self.runoff = true;
-                                    self.rf.complete().unwrap();
-                                },
-                                Err(_) => unreachable!()
-                            }
-                        }
-                    }
+                if let Some(value) = accumulated(&mut self.rf).pop() {
+                    return Some(Ok(value))
                 }
-                if self.runoff && self.buffer.borrow().is_empty() {
-                    return None
+                if self.finished {
+                    return self.error.take().map(Err)
                 }
-                match self.buffer.borrow_mut().pop_front() {
-                    None => (),
-                    Some(value) => return Some(value)
+                let result = match self.underlying.next() {
+                    None => Ok(StepResult::Stop),
+                    Some(value) => self.rf.step(value)
+                };
+                match result {
+                    Ok(StepResult::Continue) => (),
+                    Ok(StepResult::Stop) => {
+                        self.finished = true;
+                        self.error = finish(&mut self.rf, Ok(()));
+                    },
+                    Err(e) => {
+                        self.finished = true;
+                        self.error = finish(&mut self.rf, Err(e));
+                    }
                 }
             }
         }
+
+        /// Drives the underlying iterator with `try_fold`, passing on the
+        /// values produced by each step before taking the next.
+        fn fold<B, F>(self, init: B, mut f: F) -> B
+            where F: FnMut(B, Self::Item) -> B {
+
+            let TryTransduceIterator { mut underlying, mut rf, finished, mut error, .. } = self;
+            let mut acc = init;
+            while let Some(value) = accumulated(&mut rf).pop() {
+                acc = f(acc, Ok(value));
+            }
+            if !finished {
+                let flow = underlying.try_fold(acc, |mut acc, value| {
+                    let result = rf.step(value);
+                    while let Some(value) = accumulated(&mut rf).pop() {
+                        acc = f(acc, Ok(value));
+                    }
+                    match result {
+                        Ok(StepResult::Continue) => ControlFlow::Continue(acc),
+                        Ok(StepResult::Stop) => ControlFlow::Break((acc, Ok(()))),
+                        Err(e) => ControlFlow::Break((acc, Err(e)))
+                    }
+                });
+                let result = match flow {
+                    ControlFlow::Continue(folded) => {
+                        acc = folded;
+                        Ok(())
+                    },
+                    ControlFlow::Break((folded, result)) => {
+                        acc = folded;
+                        result
+                    }
+                };
+                error = finish(&mut rf, result);
+                while let Some(value) = accumulated(&mut rf).pop() {
+                    acc = f(acc, Ok(value));
+                }
+            }
+            match error {
+                Some(e) => f(acc, Err(e)),
+                None => acc
+            }
+        }
     }
 }
 
 pub mod channels {
+    use std::collections::VecDeque;
     use std::marker::PhantomData;
-    use std::sync::mpsc::{Receiver, Sender, SendError, channel};
+    use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+    use std::sync::mpsc::{Receiver, Sender, SyncSender, SendError, TrySendError, RecvError,
+                          TryRecvError, RecvTimeoutError, channel, sync_channel};
+    use std::time::{Duration, Instant};
 
     use ::{Transducer, Reducing, StepResult};
 
-    pub struct TransducingSender<O, SR>
-        where SR: Reducing<O, (), SendError<O>> {
+    /// An error from a channel application, either from the channel itself
+    /// or returned by the reducing function.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum TransducingError<C, E> {
+        Channel(C),
+        Reducing(E)
+    }
+
+    /// Wraps a `Sender`, values of type I sent are passed through the
+    /// reducing function, and any resulting values of type O sent to the
+    /// channel.  A send error contains the value of type O that could not be
+    /// sent.
+    pub struct TransducingSender<I, O, SR>
+        where SR: Reducing<I, (), SendError<O>> {
 
         rf: SR,
-        o_type: PhantomData<O>
+        stopped: bool,
+        closed: bool,
+        completion: Option<Completion>,
+        types: PhantomData<(I, O)>
+    }
+
+    struct CompletionState<E> {
+        result: Mutex<Option<Result<(), E>>>,
+        done: Condvar
+    }
+
+    /// Reports the completion of a sender, whether it was closed or dropped,
+    /// and can be sent to other threads to wait for it.  Errors are reported
+    /// without the values that could not be sent.
+    pub struct Completion<E = SendError<()>>(Arc<CompletionState<E>>);
+
+    impl<E> Clone for Completion<E> {
+        fn clone(&self) -> Self {
+            Completion(self.0.clone())
+        }
+    }
+
+    impl<E> Default for Completion<E> {
+        fn default() -> Self {
+            Completion(Arc::new(CompletionState {
+                result: Mutex::new(None),
+                done: Condvar::new()
+            }))
+        }
+    }
+
+    impl<E> Completion<E> {
+        fn set(&self, result: Result<(), E>) {
+            *self.0.result.lock().unwrap() = Some(result);
+            self.0.done.notify_all();
+        }
+
+        pub fn is_complete(&self) -> bool {
+            self.0.result.lock().unwrap().is_some()
+        }
+    }
+
+    impl<E> Completion<E>
+        where E: Clone {
+
+        /// Blocks until the sender has completed, returning an error if the
+        /// receiver had disconnected.  Never returns if the sender is leaked
+        /// rather than dropped.
+        pub fn wait(&self) -> Result<(), E> {
+            let mut result = self.0.result.lock().unwrap();
+            loop {
+                if let Some(ref result) = *result {
+                    return result.clone()
+                }
+                result = self.0.done.wait(result).unwrap();
+            }
+        }
     }
 
     pub struct SenderReducer<T>(Sender<T>);
@@ -230,31 +502,522 @@ pub mod channels {
         }
     }
 
-    impl<O, SR> TransducingSender<O, SR>
-        where SR: Reducing<O, (), SendError<O>> {
+    impl<I, O, SR> TransducingSender<I, O, SR>
+        where SR: Reducing<I, (), SendError<O>> {
+
+        fn new(rf: SR) -> Self {
+            TransducingSender {
+                rf,
+                stopped: false,
+                closed: false,
+                completion: None,
+                types: PhantomData
+            }
+        }
+
+        fn complete(&mut self) -> Result<(), SendError<O>> {
+            self.closed = true;
+            let result = self.rf.complete();
+            if let Some(ref completion) = self.completion {
+                completion.set(result.as_ref().map(|_| ()).map_err(|_| SendError(())));
+            }
+            result
+        }
 
-        pub fn send(&mut self, f: O) -> Result<bool, SendError<O>> {
+        /// A handle to observe the completion of this sender.
+        pub fn completion(&mut self) -> Completion {
+            self.completion.get_or_insert_with(Completion::default).clone()
+        }
+
+        /// Returns `Ok(false)` once the reducing function has stopped (e.g. a
+        /// `take` has been satisfied), any further values are ignored.
+        pub fn send(&mut self, f: I) -> Result<bool, SendError<O>> {
+            if self.stopped {
+                return Ok(false)
+            }
             match self.rf.step(f) {
                 Ok(StepResult::Continue) => Ok(true),
-                Ok(StepResult::Stop) => Ok(false),
+                Ok(StepResult::Stop) => {
+                    self.stopped = true;
+                    Ok(false)
+                },
                 Err(e) => Err(e)
             }
         }
 
-        pub fn close(&mut self) -> Result<(), SendError<O>> {
-            self.rf.complete()
+        /// Completes the reducing function, flushing any remaining state.
+        /// A sender that is dropped without being closed is completed then,
+        /// with the result only available from its `Completion`.
+        pub fn close(mut self) -> Result<(), SendError<O>> {
+            self.complete()
         }
     }
 
-    pub fn transducing_channel<I, O, T, RO>(transducer: T) -> (TransducingSender<I, RO>,
+    impl<I, O, SR> Drop for TransducingSender<I, O, SR>
+        where SR: Reducing<I, (), SendError<O>> {
+
+        fn drop(&mut self) {
+            if !self.closed {
+                let _ = self.complete();
+            }
+        }
+    }
+
+    pub fn transducing_channel<I, O, T, RO>(transducer: T) -> (TransducingSender<I, O, RO>,
                                                                Receiver<O>)
-        where RO: Reducing<I, (), SendError<I>, Item=O>,
+        where RO: Reducing<I, (), SendError<O>>,
               T: Transducer<SenderReducer<O>, RO=RO> {
         let (tx, rx) = channel();
-        let sender = TransducingSender {
-            rf: transducer.new(SenderReducer(tx)),
-            o_type: PhantomData
+        (TransducingSender::new(transducer.new(SenderReducer(tx))), rx)
+    }
+
+    /// Creates a `TransducingSender` for each producer from one transducer,
+    /// each with its own copy of the transducer, all sending to the same
+    /// `Receiver`.  As the state of each copy is separate, closing one sender
+    /// only flushes the values sent through it.  The receiver disconnects once
+    /// the factory, and every sender created by it, is dropped.
+    pub struct SenderFactory<T, O> {
+        transducer: T,
+        tx: Sender<O>
+    }
+
+    impl<T, O> Clone for SenderFactory<T, O>
+        where T: Clone {
+
+        fn clone(&self) -> Self {
+            SenderFactory {
+                transducer: self.transducer.clone(),
+                tx: self.tx.clone()
+            }
+        }
+    }
+
+    impl<T, O> SenderFactory<T, O>
+        where T: Clone {
+
+        pub fn sender<I, RO>(&self) -> TransducingSender<I, O, RO>
+            where RO: Reducing<I, (), SendError<O>>,
+                  T: Transducer<SenderReducer<O>, RO=RO> {
+            TransducingSender::new(self.transducer.clone().new(SenderReducer(self.tx.clone())))
+        }
+    }
+
+    /// As `transducing_channel`, but returns a `SenderFactory` so that many
+    /// producers, each with their own copy of the transducer, can send to one
+    /// `Receiver`.
+    pub fn fan_in_channel<O, T>(transducer: T) -> (SenderFactory<T, O>, Receiver<O>)
+        where T: Clone {
+        let (tx, rx) = channel();
+        (SenderFactory { transducer, tx }, rx)
+    }
+
+    /// As `TransducingSender`, but can be cloned and shared between threads
+    /// in the same way as a `Sender`.  All clones share one reducing
+    /// function, each value is passed through it in turn, so stateful
+    /// transducers see the values of all senders in the order they were
+    /// sent.  The reducing function is completed once the last clone is
+    /// dropped or closed.  A clone that panics while sending does not stop
+    /// the others, they carry on with the reducing function as it was left.
+    pub struct SharedTransducingSender<I, O, SR>
+        where SR: Reducing<I, (), SendError<O>> {
+
+        sender: Arc<Mutex<TransducingSender<I, O, SR>>>
+    }
+
+    impl<I, O, SR> Clone for SharedTransducingSender<I, O, SR>
+        where SR: Reducing<I, (), SendError<O>> {
+
+        fn clone(&self) -> Self {
+            SharedTransducingSender {
+                sender: self.sender.clone()
+            }
+        }
+    }
+
+    impl<I, O, SR> SharedTransducingSender<I, O, SR>
+        where SR: Reducing<I, (), SendError<O>> {
+
+        fn lock(&self) -> MutexGuard<'_, TransducingSender<I, O, SR>> {
+            self.sender.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+
+        /// Returns `Ok(false)` once the reducing function has stopped, any
+        /// further values from any clone are ignored.
+        pub fn send(&self, value: I) -> Result<bool, SendError<O>> {
+            self.lock().send(value)
+        }
+
+        /// A handle to observe the completion of the reducing function shared
+        /// by this sender and its clones.
+        pub fn completion(&self) -> Completion {
+            self.lock().completion()
+        }
+
+        /// Drops this sender.  If it is the last, the reducing function is
+        /// completed and the result returned, otherwise that happens when the
+        /// last of the others is dropped.
+        pub fn close(self) -> Result<(), SendError<O>> {
+            match Arc::try_unwrap(self.sender) {
+                Ok(sender) => sender.into_inner().unwrap_or_else(PoisonError::into_inner).close(),
+                Err(_) => Ok(())
+            }
+        }
+    }
+
+    /// As `transducing_channel`, but the sender can be cloned, see
+    /// `SharedTransducingSender`.
+    pub fn shared_transducing_channel<I, O, T, RO>(transducer: T) -> (SharedTransducingSender<I, O, RO>,
+                                                                      Receiver<O>)
+        where RO: Reducing<I, (), SendError<O>>,
+              T: Transducer<SenderReducer<O>, RO=RO> {
+        let (tx, rx) = transducing_channel(transducer);
+        let sender = SharedTransducingSender {
+            sender: Arc::new(Mutex::new(tx))
+        };
+        (sender, rx)
+    }
+
+    /// Wraps a `SyncSender`, values of type I sent are passed through the
+    /// reducing function, and any resulting values of type O sent to the
+    /// bounded channel.  Values produced by the reducing function wait in a
+    /// queue until there is room in the channel, so values expanded by
+    /// transducers such as `mapcat` are never lost.
+    pub struct TransducingSyncSender<I, O, E, SR>
+        where SR: Reducing<I, (), E> {
+
+        tx: SyncSender<O>,
+        rf: SR,
+        pending: Arc<Mutex<VecDeque<O>>>,
+        stopped: bool,
+        closed: bool,
+        completion: Option<Completion<TransducingError<TrySendError<()>, ()>>>,
+        types: PhantomData<(I, E)>
+    }
+
+    impl<I, O, E, SR> TransducingSyncSender<I, O, E, SR>
+        where SR: Reducing<I, (), E> {
+
+        fn step(&mut self, value: I) -> Result<(), E> {
+            self.stopped = self.rf.step(value)?.is_stop();
+            Ok(())
+        }
+
+        fn complete(&mut self) -> Result<(), E> {
+            self.closed = true;
+            self.rf.complete()
+        }
+
+        fn report(&self, result: Result<(), TransducingError<TrySendError<()>, ()>>) {
+            if let Some(ref completion) = self.completion {
+                completion.set(result);
+            }
+        }
+
+        /// A handle to observe the completion of this sender.  A sender that
+        /// was dropped reports `TrySendError::Full` if values it produced were
+        /// lost because the channel had no room for them.
+        pub fn completion(&mut self) -> Completion<TransducingError<TrySendError<()>, ()>> {
+            self.completion.get_or_insert_with(Completion::default).clone()
+        }
+
+        /// Sends as many pending values as there is room for.  A value that
+        /// cannot be sent stays at the front of the queue.
+        fn try_flush(&mut self) -> Result<(), TrySendError<()>> {
+            let mut pending = self.pending.lock().unwrap();
+            while let Some(value) = pending.pop_front() {
+                match self.tx.try_send(value) {
+                    Ok(()) => (),
+                    Err(TrySendError::Full(value)) => {
+                        pending.push_front(value);
+                        return Err(TrySendError::Full(()))
+                    },
+                    Err(TrySendError::Disconnected(value)) => {
+                        pending.push_front(value);
+                        return Err(TrySendError::Disconnected(()))
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Sends all pending values, blocking until there is room for each.
+        pub fn flush(&mut self) -> Result<(), SendError<O>> {
+            loop {
+                let value = self.pending.lock().unwrap().pop_front();
+                match value {
+                    Some(value) => self.tx.send(value)?,
+                    None => return Ok(())
+                }
+            }
+        }
+
+        /// The number of values produced by the reducing function that are
+        /// waiting for room in the channel.
+        pub fn pending(&self) -> usize {
+            self.pending.lock().unwrap().len()
+        }
+
+        /// Blocks until every value produced is in the channel.  Returns
+        /// `Ok(false)` once the reducing function has stopped, any further
+        /// values are ignored.
+        pub fn send(&mut self, value: I) -> Result<bool, TransducingError<SendError<O>, E>> {
+            if self.stopped {
+                return Ok(false)
+            }
+            self.step(value).map_err(TransducingError::Reducing)?;
+            self.flush().map_err(TransducingError::Channel)?;
+            Ok(!self.stopped)
+        }
+
+        /// Returns `TrySendError::Full` with the value, without passing it
+        /// through the reducing function, if values produced by earlier sends
+        /// are still waiting for room in the channel.  Otherwise the value is
+        /// accepted, and as many of the values it produces as fit are sent,
+        /// the rest wait for the next `send`, `try_send`, `flush` or `close`.
+        pub fn try_send(&mut self, value: I) -> Result<bool, TransducingError<TrySendError<I>, E>> {
+            match self.try_flush() {
+                Ok(()) => (),
+                Err(TrySendError::Full(())) => return Err(TransducingError::Channel(TrySendError::Full(value))),
+                Err(TrySendError::Disconnected(())) => {
+                    return Err(TransducingError::Channel(TrySendError::Disconnected(value)))
+                }
+            }
+            if self.stopped {
+                return Ok(false)
+            }
+            self.step(value).map_err(TransducingError::Reducing)?;
+            // A disconnection is reported by the next call
+            let _ = self.try_flush();
+            Ok(!self.stopped)
+        }
+
+        /// Completes the reducing function and blocks until every value
+        /// produced is in the channel.  A sender that is dropped without
+        /// being closed is completed then, but does not block: values that do
+        /// not fit in the channel are dropped, and its `Completion` reports
+        /// the loss.
+        pub fn close(mut self) -> Result<(), TransducingError<SendError<O>, E>> {
+            let result = match self.complete() {
+                Ok(()) => self.flush().map_err(TransducingError::Channel),
+                Err(e) => Err(TransducingError::Reducing(e))
+            };
+            self.report(match result {
+                Ok(()) => Ok(()),
+                Err(TransducingError::Channel(_)) => Err(TransducingError::Channel(TrySendError::Disconnected(()))),
+                Err(TransducingError::Reducing(_)) => Err(TransducingError::Reducing(()))
+            });
+            result
+        }
+    }
+
+    /// Dropping may happen on the thread that owns the receiver, or while
+    /// unwinding from a panic, so it never waits for room in the channel.
+    impl<I, O, E, SR> Drop for TransducingSyncSender<I, O, E, SR>
+        where SR: Reducing<I, (), E> {
+
+        fn drop(&mut self) {
+            if !self.closed {
+                let result = match self.complete() {
+                    Ok(()) => self.try_flush().map_err(TransducingError::Channel),
+                    Err(_) => Err(TransducingError::Reducing(()))
+                };
+                self.report(result);
+            }
+        }
+    }
+
+    /// As `transducing_channel`, but the channel can hold at most `bound`
+    /// values, see `TransducingSyncSender`.  Unless the transducer fixes it,
+    /// the error type of the reducing function needs to be given, e.g. `()`
+    /// for transducers that cannot fail.
+    pub fn transducing_sync_channel<I, O, E, T, RO>(bound: usize, transducer: T) -> (TransducingSyncSender<I, O, E, RO>,
+                                                                                   Receiver<O>)
+        where RO: Reducing<I, (), E>,
+              T: Transducer<QueueReducer<O>, RO=RO> {
+        let (tx, rx) = sync_channel(bound);
+        let pending = Arc::new(Mutex::new(VecDeque::new()));
+        let sender = TransducingSyncSender {
+            tx,
+            rf: transducer.new(QueueReducer(pending.clone())),
+            pending,
+            stopped: false,
+            closed: false,
+            completion: None,
+            types: PhantomData
         };
         (sender, rx)
     }
+
+    /// Collects values into a queue shared with the channel application that
+    /// created it, which takes them from the front.
+    pub struct QueueReducer<T>(Arc<Mutex<VecDeque<T>>>);
+
+    impl<T, E> Reducing<T, (), E> for QueueReducer<T> {
+        type Item = T;
+
+        #[inline]
+        fn step(&mut self, value: T) -> Result<StepResult, E> {
+            self.0.lock().unwrap().push_back(value);
+            Ok(StepResult::Continue)
+        }
+
+        fn complete(&mut self) -> Result<(), E> {
+            Ok(())
+        }
+    }
+
+    /// Wraps a `Receiver`, values of type I received are passed through the
+    /// reducing function, and any resulting values of type O returned.  The
+    /// reducing function is completed once the channel disconnects, or it
+    /// stops, after which any remaining values are returned before reporting
+    /// the disconnection.  An error from the reducing function ends the
+    /// reduction in the same way.
+    pub struct TransducingReceiver<I, O, E, RR>
+        where RR: Reducing<I, (), E> {
+
+        rx: Receiver<I>,
+        rf: RR,
+        buffer: Arc<Mutex<VecDeque<O>>>,
+        done: bool,
+        error: PhantomData<E>
+    }
+
+    impl<I, O, E, RR> TransducingReceiver<I, O, E, RR>
+        where RR: Reducing<I, (), E> {
+
+        fn pop(&self) -> Option<O> {
+            self.buffer.lock().unwrap().pop_front()
+        }
+
+        fn step(&mut self, value: I) -> Result<(), E> {
+            match self.rf.step(value) {
+                Ok(StepResult::Continue) => Ok(()),
+                Ok(StepResult::Stop) => self.finish(),
+                Err(e) => {
+                    self.done = true;
+                    Err(e)
+                }
+            }
+        }
+
+        fn finish(&mut self) -> Result<(), E> {
+            self.done = true;
+            self.rf.complete()
+        }
+
+        pub fn recv(&mut self) -> Result<O, TransducingError<RecvError, E>> {
+            loop {
+                if let Some(value) = self.pop() {
+                    return Ok(value)
+                }
+                if self.done {
+                    return Err(TransducingError::Channel(RecvError))
+                }
+                match self.rx.recv() {
+                    Ok(value) => self.step(value),
+                    Err(RecvError) => self.finish()
+                }.map_err(TransducingError::Reducing)?;
+            }
+        }
+
+        pub fn try_recv(&mut self) -> Result<O, TransducingError<TryRecvError, E>> {
+            loop {
+                if let Some(value) = self.pop() {
+                    return Ok(value)
+                }
+                if self.done {
+                    return Err(TransducingError::Channel(TryRecvError::Disconnected))
+                }
+                match self.rx.try_recv() {
+                    Ok(value) => self.step(value),
+                    Err(TryRecvError::Empty) => return Err(TransducingError::Channel(TryRecvError::Empty)),
+                    Err(TryRecvError::Disconnected) => self.finish()
+                }.map_err(TransducingError::Reducing)?;
+            }
+        }
+
+        /// The timeout covers the whole call, however many values need to be
+        /// received before one is produced.
+        pub fn recv_timeout(&mut self, timeout: Duration) -> Result<O, TransducingError<RecvTimeoutError, E>> {
+            let deadline = Instant::now() + timeout;
+            loop {
+                if let Some(value) = self.pop() {
+                    return Ok(value)
+                }
+                if self.done {
+                    return Err(TransducingError::Channel(RecvTimeoutError::Disconnected))
+                }
+                match self.rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(value) => self.step(value),
+                    Err(RecvTimeoutError::Timeout) => return Err(TransducingError::Channel(RecvTimeoutError::Timeout)),
+                    Err(RecvTimeoutError::Disconnected) => self.finish()
+                }.map_err(TransducingError::Reducing)?;
+            }
+        }
+
+        /// An iterator that blocks waiting for each value, ending once the
+        /// channel disconnects or the reducing function returns an error,
+        /// which can be seen by calling `recv` instead.
+        pub fn iter(&mut self) -> Iter<'_, I, O, E, RR> {
+            Iter(self)
+        }
+    }
+
+    pub struct Iter<'a, I, O, E, RR>(&'a mut TransducingReceiver<I, O, E, RR>)
+        where RR: Reducing<I, (), E> + 'a,
+              I: 'a,
+              O: 'a,
+              E: 'a;
+
+    impl<'a, I, O, E, RR> Iterator for Iter<'a, I, O, E, RR>
+        where RR: Reducing<I, (), E> {
+
+        type Item = O;
+
+        fn next(&mut self) -> Option<O> {
+            self.0.recv().ok()
+        }
+    }
+
+    pub struct IntoIter<I, O, E, RR>(TransducingReceiver<I, O, E, RR>)
+        where RR: Reducing<I, (), E>;
+
+    impl<I, O, E, RR> Iterator for IntoIter<I, O, E, RR>
+        where RR: Reducing<I, (), E> {
+
+        type Item = O;
+
+        fn next(&mut self) -> Option<O> {
+            self.0.recv().ok()
+        }
+    }
+
+    impl<I, O, E, RR> IntoIterator for TransducingReceiver<I, O, E, RR>
+        where RR: Reducing<I, (), E> {
+
+        type Item = O;
+        type IntoIter = IntoIter<I, O, E, RR>;
+
+        fn into_iter(self) -> Self::IntoIter {
+            IntoIter(self)
+        }
+    }
+
+    /// Applies a transducer to the values received from an existing
+    /// `Receiver`, on the receiving thread, for channels whose sending side
+    /// cannot be wrapped.  Unless the transducer fixes it, the error type of
+    /// the reducing function needs to be given, e.g. `()` for transducers
+    /// that cannot fail.
+    pub fn transducing_receiver<I, O, E, T, RR>(receiver: Receiver<I>, transducer: T) -> TransducingReceiver<I, O, E, RR>
+        where RR: Reducing<I, (), E>,
+              T: Transducer<QueueReducer<O>, RO=RR> {
+        let buffer = Arc::new(Mutex::new(VecDeque::new()));
+        TransducingReceiver {
+            rx: receiver,
+            rf: transducer.new(QueueReducer(buffer.clone())),
+            buffer,
+            done: false,
+            error: PhantomData
+        }
+    }
 }
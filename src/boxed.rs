@@ -0,0 +1,168 @@
+/*
+ * Copyright 2016 rs-transducers developers
+ *
+ * Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+ * http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+ * <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+ * option. This file may not be copied, modified, or distributed
+ * except according to those terms.
+ */
+use super::{Transducer, TransducerExt, Reducing, StepResult};
+
+/// An object-safe version of `Reducing`, implemented for every reducing
+/// function, that does not expose `Item`.
+trait DynReducing<I, O, E> {
+    fn init(&mut self);
+
+    fn step(&mut self, value: I) -> Result<StepResult, E>;
+
+    fn accumulated(&mut self) -> Option<&mut O>;
+
+    fn complete(&mut self) -> Result<O, E>;
+}
+
+impl<R, I, O, E> DynReducing<I, O, E> for R
+    where R: Reducing<I, O, E> {
+
+    fn init(&mut self) {
+        Reducing::init(self)
+    }
+
+    #[inline]
+    fn step(&mut self, value: I) -> Result<StepResult, E> {
+        Reducing::step(self, value)
+    }
+
+    #[inline]
+    fn accumulated(&mut self) -> Option<&mut O> {
+        Reducing::accumulated(self)
+    }
+
+    fn complete(&mut self) -> Result<O, E> {
+        Reducing::complete(self)
+    }
+}
+
+/// A reducing function from I to O with step errors of E, of any underlying
+/// type.
+pub struct BoxedReducing<'a, I, O, E>(Box<dyn DynReducing<I, O, E> + 'a>);
+
+impl<'a, I, O, E> BoxedReducing<'a, I, O, E> {
+    pub fn new<R>(reducing_fn: R) -> Self
+        where R: Reducing<I, O, E> + 'a {
+
+        BoxedReducing(Box::new(reducing_fn))
+    }
+}
+
+impl<'a, I, O, E> Reducing<I, O, E> for BoxedReducing<'a, I, O, E> {
+    type Item = I;
+
+    fn init(&mut self) {
+        self.0.init();
+    }
+
+    #[inline]
+    fn step(&mut self, value: I) -> Result<StepResult, E> {
+        self.0.step(value)
+    }
+
+    #[inline]
+    fn accumulated(&mut self) -> Option<&mut O> {
+        self.0.accumulated()
+    }
+
+    fn complete(&mut self) -> Result<O, E> {
+        self.0.complete()
+    }
+}
+
+/// An object-safe transducer, turning a reducing function from T to O into a
+/// reducing function from I to O.  Implemented for every cloneable transducer
+/// that can be applied to a `BoxedReducing`.
+pub trait DynTransducer<'a, I, T, O, E> {
+    fn new_boxed(&self, reducing_fn: BoxedReducing<'a, T, O, E>) -> BoxedReducing<'a, I, O, E>;
+
+    fn clone_boxed(&self) -> Box<dyn DynTransducer<'a, I, T, O, E> + 'a>;
+}
+
+impl<'a, X, I, T, O, E> DynTransducer<'a, I, T, O, E> for X
+    where X: Transducer<BoxedReducing<'a, T, O, E>> + Clone + 'a,
+          X::RO: Reducing<I, O, E> + 'a {
+
+    fn new_boxed(&self, reducing_fn: BoxedReducing<'a, T, O, E>) -> BoxedReducing<'a, I, O, E> {
+        BoxedReducing::new(self.clone().new(reducing_fn))
+    }
+
+    fn clone_boxed(&self) -> Box<dyn DynTransducer<'a, I, T, O, E> + 'a> {
+        Box::new(self.clone())
+    }
+}
+
+/// A transducer from I to T, for reducing functions returning O with step
+/// errors of E, of any underlying type.  This allows transducers to be chosen
+/// at runtime, or stored together, at the cost of a dynamic call per step.
+pub struct BoxedTransducer<'a, I, T, O, E>(Box<dyn DynTransducer<'a, I, T, O, E> + 'a>);
+
+impl<'a, I, T, O, E> BoxedTransducer<'a, I, T, O, E> {
+    pub fn new<X>(transducer: X) -> Self
+        where X: DynTransducer<'a, I, T, O, E> + 'a {
+
+        BoxedTransducer(Box::new(transducer))
+    }
+
+    fn new_boxed(&self, reducing_fn: BoxedReducing<'a, T, O, E>) -> BoxedReducing<'a, I, O, E> {
+        self.0.new_boxed(reducing_fn)
+    }
+}
+
+impl<'a, I, T, O, E> Clone for BoxedTransducer<'a, I, T, O, E> {
+    fn clone(&self) -> Self {
+        BoxedTransducer(self.0.clone_boxed())
+    }
+}
+
+impl<'a, RI, I, T, O, E> Transducer<RI> for BoxedTransducer<'a, I, T, O, E>
+    where RI: Reducing<T, O, E> + 'a {
+
+    type RO = BoxedReducing<'a, I, O, E>;
+
+    fn new(self, reducing_fn: RI) -> Self::RO {
+        self.new_boxed(BoxedReducing::new(reducing_fn))
+    }
+}
+
+impl<'a, I, T, O, E> TransducerExt for BoxedTransducer<'a, I, T, O, E> {}
+
+pub fn boxed<'a, X, I, T, O, E>(transducer: X) -> BoxedTransducer<'a, I, T, O, E>
+    where X: DynTransducer<'a, I, T, O, E> + 'a {
+
+    BoxedTransducer::new(transducer)
+}
+
+struct ChainTransducer<'a, I, O, E>(Vec<BoxedTransducer<'a, I, I, O, E>>);
+
+impl<'a, I, O, E> Clone for ChainTransducer<'a, I, O, E> {
+    fn clone(&self) -> Self {
+        ChainTransducer(self.0.clone())
+    }
+}
+
+impl<'a, I, O, E> Transducer<BoxedReducing<'a, I, O, E>> for ChainTransducer<'a, I, O, E> {
+    type RO = BoxedReducing<'a, I, O, E>;
+
+    fn new(self, reducing_fn: BoxedReducing<'a, I, O, E>) -> Self::RO {
+        self.0.iter().rev().fold(reducing_fn, |rf, t| t.new_boxed(rf))
+    }
+}
+
+/// Chain any number of boxed transducers into one, values flow through them
+/// in order.  An empty chain passes values through unchanged.
+pub fn chain<'a, S, I, O, E>(stages: S) -> BoxedTransducer<'a, I, I, O, E>
+    where S: IntoIterator<Item=BoxedTransducer<'a, I, I, O, E>>,
+          I: 'a,
+          O: 'a,
+          E: 'a {
+
+    boxed(ChainTransducer(stages.into_iter().collect()))
+}
@@ -7,14 +7,183 @@
  * option. This file may not be copied, modified, or distributed
  * except according to those terms.
  */
+/// Compose any number of transducers.  Unlike `compose`, values flow
+/// through the transducers from left to right, so `comp!(a, b, c)` applies
+/// `a` first, then `b`, then `c`.
+#[macro_export]
+macro_rules! comp {
+    ($t:expr) => ($t);
+    ($t:expr, $($rest:expr),+) => ($crate::compose($crate::comp!($($rest),+), $t));
+    ($($t:expr),+ ,) => ($crate::comp!($($t),+));
+}
+
+/// Define a transducer from its struct, the state of its reducing function,
+/// and a step function.  This generates the reducing function's struct, the
+/// `Transducer` and `TransducerExt` implementations, and a `Reducing`
+/// implementation whose `init` and `complete` forward to the next reducing
+/// function.  Within `step`, `self.t` is the transducer, `self.rf` the next
+/// reducing function, and each state field is available by name:
+///
+/// ```
+/// #[macro_use]
+/// extern crate rs_transducers;
+///
+/// use rs_transducers::StepResult;
+///
+/// transducer! {
+///     /// Passes on every `n`th value.
+///     #[derive(Clone)]
+///     pub struct TakeNthTransducer { n: usize }
+///
+///     pub struct TakeNthReducer { count: usize = 0 }
+///
+///     impl<I> Reducing for TakeNthReducer {
+///         type Item = I;
+///
+///         fn step(&mut self, value: I) -> Result<StepResult, E> {
+///             self.count += 1;
+///             if self.count % self.t.n == 0 {
+///                 self.rf.step(value)
+///             } else {
+///                 Ok(StepResult::Continue)
+///             }
+///         }
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
+/// The `Reducing` implementation is generated for the type it names, so
+/// naming anything other than the reducing function's struct fails to
+/// compile.  Type parameters of the transducer are shared by the reducing
+/// function, bounds can be given in a `where` clause after the reducing
+/// function's name.  A transducer that needs to flush its state in
+/// `complete` should implement `Reducing` itself, or use
+/// `transducers::stateful`.
+#[macro_export]
+macro_rules! transducer {
+    (
+        $(#[$tattr:meta])*
+        $tvis:vis struct $t:ident $(<$($tg:ident),+>)? { $($tf:ident: $tfty:ty),* $(,)? }
+
+        $(#[$rattr:meta])*
+        $rvis:vis struct $r:ident { $($sf:ident: $sfty:ty = $sinit:expr),* $(,)? }
+
+        impl $(<$($ig:ident),*>)? Reducing for $rimpl:ident $($rest:tt)+
+    ) => {
+        $(#[$tattr])*
+        $tvis struct $t $(<$($tg),+>)? {
+            $($tf: $tfty),*
+        }
+
+        $(#[$rattr])*
+        $rvis struct $r<RF, $($($tg),+)?> {
+            rf: RF,
+            #[allow(dead_code)]
+            t: $t $(<$($tg),+>)?,
+            $($sf: $sfty),*
+        }
+
+        impl<RF, $($($tg),+)?> $crate::Transducer<RF> for $t $(<$($tg),+>)? {
+            type RO = $r<RF, $($($tg),+)?>;
+
+            fn new(self, reducing_fn: RF) -> Self::RO {
+                $r {
+                    rf: reducing_fn,
+                    t: self,
+                    $($sf: $sinit),*
+                }
+            }
+        }
+
+        impl<$($($tg),+)?> $crate::TransducerExt for $t $(<$($tg),+>)? {}
+
+        $crate::transducer!(@reducing [$rimpl] [$($($tg),+)?] [$($($ig),*)?] [] $($rest)+);
+    };
+
+    (@reducing [$r:ident] [$($tg:ident),*] [$($ig:ident),*] [$($wc:tt)*] {
+        type Item = $item:ty;
+
+        fn step(&mut $self:ident, $value:ident: $ity:ty) -> Result<StepResult, $e:ident> $body:block
+    }) => {
+        impl<RF, OF, $e, $($tg,)* $($ig),*> $crate::Reducing<$ity, OF, $e> for $r<RF, $($tg),*>
+            where RF: $crate::Reducing<$item, OF, $e>,
+                  $($wc)* {
+
+            type Item = $item;
+
+            fn init(&mut self) {
+                $crate::Reducing::init(&mut self.rf);
+            }
+
+            #[inline]
+            fn step(&mut $self, $value: $ity) -> Result<$crate::StepResult, $e> $body
+
+            #[inline]
+            fn accumulated(&mut self) -> Option<&mut OF> {
+                $crate::Reducing::accumulated(&mut self.rf)
+            }
+
+            fn complete(&mut self) -> Result<OF, $e> {
+                $crate::Reducing::complete(&mut self.rf)
+            }
+        }
+    };
+
+    (@reducing [$r:ident] [$($tg:ident),*] [$($ig:ident),*] [] where $($rest:tt)+) => {
+        $crate::transducer!(@reducing [$r] [$($tg),*] [$($ig),*] [] $($rest)+);
+    };
+
+    (@reducing [$r:ident] [$($tg:ident),*] [$($ig:ident),*] [$($wc:tt)*] $next:tt $($rest:tt)+) => {
+        $crate::transducer!(@reducing [$r] [$($tg),*] [$($ig),*] [$($wc)* $next] $($rest)+);
+    };
+}
+
 pub mod transducers;
+pub mod reducers;
+pub mod boxed;
+pub mod random;
 pub mod applications;
 
+use std::collections::HashMap;
+
+use boxed::{BoxedTransducer, DynTransducer};
+use random::{Rng, Reseeding};
+use transducers::{MapTransducer, MapIndexedTransducer, ScanTransducer, MapcatTransducer,
+                  TryMapTransducer, FilterTransducer, TryFilterTransducer, KeepTransducer,
+                  KeepIndexedTransducer, TryKeepTransducer, PartitionTransducer,
+                  PartitionByTransducer, WindowTransducer, PairwiseTransducer, TakeTransducer,
+                  TakeWhileTransducer, DropTransducer, DropWhileTransducer, ReplaceTransducer,
+                  InterposeTransducer, DedupeTransducer, DistinctTransducer, DistinctByTransducer,
+                  DistinctBoundedTransducer, DistinctWithinTransducer, RandomSampleTransducer,
+                  StatefulTransducer, Emitter};
+
+/// The result of each step of a reducing function, the equivalent of
+/// Clojure's `reduced`.
+///
+/// Once a reducing function returns `Stop` it will accept no more values:
+///
+/// 1. An application must not call `step` again, but must still call
+///    `complete` exactly once.  Stateful transducers may flush values they
+///    hold (e.g. the final partition of `partition_all`) during `complete`.
+/// 2. A transducer receiving `Stop` from the underlying reducing function
+///    must return `Stop` from its own `step`, without passing on any further
+///    values.  Any value it has not yet passed on is dropped, and is not
+///    flushed by `complete`.
+/// 3. The result of any `step` made during `complete` is ignored, other
+///    than errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum StepResult {
     Continue,
     Stop
 }
 
+impl StepResult {
+    pub fn is_stop(&self) -> bool {
+        *self == StepResult::Stop
+    }
+}
+
 /// Defines a reducing function from I to O with step errors of E
 pub trait Reducing<I, O, E> {
     /// The type of each value after the reducing function
@@ -24,22 +193,47 @@ pub trait Reducing<I, O, E> {
     /// TODO: may not be required at all. Not currently used by any implementation
     fn init(&mut self) {}
 
-    /// Each step, may fail
-    /// TODO: the return type to contain an indicator of early termination
+    /// Each step, may fail.  Returns `StepResult::Stop` to end the reduction
+    /// early, see `StepResult` for what this requires.
     fn step(&mut self, value: I) -> Result<StepResult, E>;
 
-    /// Transducers must call the underlying `complete`
-    fn complete(&mut self) -> Result<(), E>;
+    /// The value accumulated so far, for applications that take values out
+    /// before `complete`, such as the lazy iterator.  Reducing functions that
+    /// only produce their value in `complete` return `None`.  Transducers must
+    /// return the underlying `accumulated`.
+    fn accumulated(&mut self) -> Option<&mut O> {
+        None
+    }
+
+    /// Transducers must call the underlying `complete`, returning the final
+    /// value it produces.  Called once, at the end of the reduction, whether
+    /// or not it ended early.
+    fn complete(&mut self) -> Result<O, E>;
 }
 
 /// Defines a transducer that transforms a reducing function RI into
 /// a reducing function RO
 pub trait Transducer<RI> {
     type RO;
+    #[allow(clippy::wrong_self_convention)]
     fn new(self, reducing_fn: RI) -> Self::RO;
 }
 
+/// A reference to a cloneable transducer is itself a transducer, this allows
+/// the same transducer to be applied many times without being rebuilt.  Each
+/// application works on a fresh copy, so no state is shared between them.
+impl<RI, T> Transducer<RI> for &T
+    where T: Transducer<RI> + Clone {
+
+    type RO = T::RO;
+
+    fn new(self, reducing_fn: RI) -> Self::RO {
+        self.clone().new(reducing_fn)
+    }
+}
+
 /// Composed transducers
+#[derive(Clone)]
 pub struct ComposedTransducer<AT, BT> {
     a: AT,
     b: BT
@@ -57,20 +251,205 @@ impl <RI, RT, RO, AT, BT> Transducer<RI> for ComposedTransducer<AT, BT>
 
 pub fn compose<AT, BT>(a: AT, b: BT) -> ComposedTransducer<AT, BT> {
     ComposedTransducer {
-        a: a,
-        b: b
+        a,
+        b
     }
 }
 
+/// Methods for chaining transducers together.  Each method composes the given
+/// transducer after `self`, so values flow through the chain in the order it
+/// is written: `map(f).filter(g).take(10)`.
+pub trait TransducerExt: Sized {
+    fn then<T>(self, next: T) -> ComposedTransducer<T, Self> {
+        compose(next, self)
+    }
+
+    fn map<F, I, O>(self, f: F) -> ComposedTransducer<MapTransducer<F>, Self>
+        where F: Fn(I) -> O {
+        self.then(transducers::map(f))
+    }
+
+    fn map_indexed<F, I, O>(self, f: F) -> ComposedTransducer<MapIndexedTransducer<F>, Self>
+        where F: Fn(usize, I) -> O {
+        self.then(transducers::map_indexed(f))
+    }
+
+    fn try_map<F, I, O, FE>(self, f: F) -> ComposedTransducer<TryMapTransducer<F>, Self>
+        where F: Fn(I) -> Result<O, FE> {
+        self.then(transducers::try_map(f))
+    }
+
+    fn scan<S, F, I>(self, init: S, f: F) -> ComposedTransducer<ScanTransducer<S, F>, Self>
+        where F: Fn(&S, I) -> S {
+        self.then(transducers::scan(init, f))
+    }
+
+    fn mapcat<F, I, O, IO>(self, f: F) -> ComposedTransducer<MapcatTransducer<F>, Self>
+        where IO: IntoIterator<Item=O>,
+              F: Fn(I) -> IO {
+        self.then(transducers::mapcat(f))
+    }
+
+    fn filter<F, T>(self, f: F) -> ComposedTransducer<FilterTransducer<F>, Self>
+        where F: Fn(&T) -> bool {
+        self.then(transducers::filter(f))
+    }
+
+    fn remove<F, T>(self, f: F) -> ComposedTransducer<FilterTransducer<F>, Self>
+        where F: Fn(&T) -> bool {
+        self.then(transducers::remove(f))
+    }
+
+    fn try_filter<F, T, FE>(self, f: F) -> ComposedTransducer<TryFilterTransducer<F>, Self>
+        where F: Fn(&T) -> Result<bool, FE> {
+        self.then(transducers::try_filter(f))
+    }
+
+    fn keep<F, I, O>(self, f: F) -> ComposedTransducer<KeepTransducer<F>, Self>
+        where F: Fn(I) -> Option<O> {
+        self.then(transducers::keep(f))
+    }
+
+    fn keep_indexed<F, I, O>(self, f: F) -> ComposedTransducer<KeepIndexedTransducer<F>, Self>
+        where F: Fn(usize, I) -> Option<O> {
+        self.then(transducers::keep_indexed(f))
+    }
+
+    fn try_keep<F, I, O, FE>(self, f: F) -> ComposedTransducer<TryKeepTransducer<F>, Self>
+        where F: Fn(I) -> Result<Option<O>, FE> {
+        self.then(transducers::try_keep(f))
+    }
+
+    fn partition<T>(self, num: usize) -> ComposedTransducer<PartitionTransducer<T>, Self> {
+        self.then(transducers::partition(num))
+    }
+
+    fn partition_all<T>(self, num: usize) -> ComposedTransducer<PartitionTransducer<T>, Self> {
+        self.then(transducers::partition_all(num))
+    }
+
+    fn partition_by<F, T, R>(self, f: F) -> ComposedTransducer<PartitionByTransducer<F, T, R>, Self>
+        where F: Fn(&T) -> R {
+        self.then(transducers::partition_by(f))
+    }
+
+    fn window<T>(self, size: usize, step: usize) -> ComposedTransducer<WindowTransducer<T>, Self> {
+        self.then(transducers::window(size, step))
+    }
+
+    fn pairwise<T>(self) -> ComposedTransducer<PairwiseTransducer<T>, Self> {
+        self.then(transducers::pairwise())
+    }
+
+    fn take(self, num: usize) -> ComposedTransducer<TakeTransducer, Self> {
+        self.then(transducers::take(num))
+    }
+
+    fn take_while<F, T>(self, pred: F) -> ComposedTransducer<TakeWhileTransducer<F>, Self>
+        where F: Fn(&T) -> bool {
+        self.then(transducers::take_while(pred))
+    }
+
+    fn drop(self, num: usize) -> ComposedTransducer<DropTransducer, Self> {
+        self.then(transducers::drop(num))
+    }
+
+    fn drop_while<F, T>(self, pred: F) -> ComposedTransducer<DropWhileTransducer<F>, Self>
+        where F: Fn(&T) -> bool {
+        self.then(transducers::drop_while(pred))
+    }
+
+    fn replace<T>(self, replacements: HashMap<T, T>) -> ComposedTransducer<ReplaceTransducer<T>, Self> {
+        self.then(transducers::replace(replacements))
+    }
+
+    fn interpose<T>(self, separator: T) -> ComposedTransducer<InterposeTransducer<T>, Self> {
+        self.then(transducers::interpose(separator))
+    }
+
+    fn dedupe<T>(self) -> ComposedTransducer<DedupeTransducer<T>, Self> {
+        self.then(transducers::dedupe())
+    }
+
+    fn distinct<T>(self) -> ComposedTransducer<DistinctTransducer<T>, Self> {
+        self.then(transducers::distinct())
+    }
+
+    fn distinct_by<F, T, K>(self, key_fn: F) -> ComposedTransducer<DistinctByTransducer<F, T, K>, Self>
+        where F: Fn(&T) -> K {
+        self.then(transducers::distinct_by(key_fn))
+    }
+
+    fn distinct_bounded<T>(self, capacity: usize) -> ComposedTransducer<DistinctBoundedTransducer<T>, Self> {
+        self.then(transducers::distinct_bounded(capacity))
+    }
+
+    fn distinct_within<T>(self, n: usize) -> ComposedTransducer<DistinctWithinTransducer<T>, Self> {
+        self.then(transducers::distinct_within(n))
+    }
+
+    fn random_sample(self, prob: f64) -> ComposedTransducer<RandomSampleTransducer<Reseeding>, Self> {
+        self.then(transducers::random_sample(prob))
+    }
+
+    fn random_sample_with<G>(self, prob: f64, rng: G) -> ComposedTransducer<RandomSampleTransducer<G>, Self>
+        where G: Rng {
+        self.then(transducers::random_sample_with(prob, rng))
+    }
+
+    fn stateful<S, I, O, FS, FC>(self, init_state: S, step_fn: FS, complete_fn: FC)
+                                 -> ComposedTransducer<StatefulTransducer<S, FS, FC, O>, Self>
+        where FS: Fn(&mut S, I, &mut Emitter<O>),
+              FC: Fn(&mut S, &mut Emitter<O>) {
+        self.then(transducers::stateful(init_state, step_fn, complete_fn))
+    }
+
+    fn boxed<'a, I, T, O, E>(self) -> BoxedTransducer<'a, I, T, O, E>
+        where Self: DynTransducer<'a, I, T, O, E> + 'a {
+        boxed::boxed(self)
+    }
+}
+
+impl<AT, BT> TransducerExt for ComposedTransducer<AT, BT> {}
+
+/// Apply a transducer to a reducing function, then reduce each value of
+/// `source` with it, returning the final value produced by the reducing
+/// function.  Stops early if any step returns `StepResult::Stop`.
+pub fn transduce<T, R, RO, S, I, O, E>(transducer: T, reducing_fn: R, source: S) -> Result<O, E>
+    where S: IntoIterator<Item=I>,
+          RO: Reducing<I, O, E>,
+          T: Transducer<R, RO=RO> {
+
+    let mut reducing = transducer.new(reducing_fn);
+    reducing.init();
+    for val in source {
+        match reducing.step(val) {
+            Ok(StepResult::Continue) => (),
+            Ok(StepResult::Stop) => break,
+            Err(e) => return Err(e)
+        }
+    }
+    reducing.complete()
+}
+
 #[cfg(test)]
 mod test {
-    use std::collections::HashMap;
+    use std::collections::{BTreeSet, HashMap};
+    use std::mem;
+    use std::num::ParseIntError;
     use std::thread;
 
-    use super::transducers;
+    use super::transducers::{self, Emitter};
+    use super::reducers;
+    use super::{TransducerExt, Reducing};
+    use super::boxed::{self, BoxedTransducer};
+    use super::random::SplitMix64;
     use super::applications::vec::{Into, Ref};
     use super::applications::iter::TransduceIter;
-    use super::applications::channels::transducing_channel;
+    use super::applications::collect::Collect;
+    use super::applications::channels::{transducing_channel, transducing_receiver, transducing_sync_channel,
+                                        shared_transducing_channel, fan_in_channel, TransducingReceiver,
+                                        TransducingSyncSender, TransducingError};
 
     #[test]
     fn test_vec_ref() {
@@ -103,6 +482,398 @@ mod test {
         assert_eq!(vec![1, 1, 2, 2, 3, 3], result);
     }
 
+    #[test]
+    fn test_iterator_next_and_fold() {
+        let transducer = transducers::mapcat(duplicator)
+            .partition_all(3)
+            .take(3);
+        let mut iter = (1..10).transduce(&transducer);
+        assert_eq!(Some(vec![1, 1, 2]), iter.next());
+        let rest:Vec<Vec<isize>> = iter.collect();
+        assert_eq!(vec![vec![2, 3, 3], vec![4, 4, 5]], rest);
+
+        let transducer = transducers::mapcat(duplicator).partition_all(4);
+        let mut iter = (1..4).transduce(transducer.boxed());
+        assert_eq!(Some(vec![1, 1, 2, 2]), iter.next());
+        assert_eq!(Some(vec![3, 3]), iter.next());
+        assert_eq!(None, iter.next());
+    }
+
+    #[test]
+    fn test_reuse() {
+        let transducer = super::compose(transducers::partition_all(2),
+                                        transducers::filter(|x| x % 2 == 0));
+        let batches = vec![vec![1, 2, 3, 4, 5, 6], vec![8, 10, 11, 12], vec![14]];
+        let results:Vec<Vec<Vec<usize>>> = batches.into_iter()
+            .map(|batch| batch.transduce_into(&transducer).unwrap())
+            .collect();
+        assert_eq!(vec![vec![vec![2, 4], vec![6]],
+                        vec![vec![8, 10], vec![12]],
+                        vec![vec![14]]], results);
+
+        let source = vec![1, 2, 3, 4, 5, 6];
+        let result:Vec<Vec<usize>> = source.into_iter().transduce(transducer.clone()).collect();
+        assert_eq!(vec![vec![2, 4], vec![6]], result);
+    }
+
+    #[test]
+    fn test_transduce() {
+        let source = vec![1, 2, 3, 4, 5];
+        let transducer = transducers::filter(|x| x % 2 == 1);
+        let result:Result<_, ()> = super::transduce(transducer, reducers::fold(0, |acc, x| acc + x), source);
+        assert_eq!(Ok(9), result);
+
+        let source = vec!["a", "bb", "cc", "ddd"];
+        let transducer = transducers::map(|x: &'static str| (x.len(), x));
+        let by_length = reducers::fold(HashMap::new(), |mut acc: HashMap<usize, Vec<&str>>, (k, v)| {
+            acc.entry(k).or_default().push(v);
+            acc
+        });
+        let result:Result<_, ()> = super::transduce(transducer, by_length, source);
+        let result = result.unwrap();
+        assert_eq!(3, result.len());
+        assert_eq!(vec!["bb", "cc"], result[&2]);
+    }
+
+    #[test]
+    fn test_aggregates() {
+        fn run<R, O>(reducing_fn: R, source: Vec<isize>) -> O
+            where R: Reducing<isize, O, ()> {
+            super::transduce(transducers::map(|x| x), reducing_fn, source).unwrap()
+        }
+
+        let source = vec![3, 1, 4, 1, 5, 9, 2, 6];
+        assert_eq!(8, run(reducers::count(), source.clone()));
+        assert_eq!(31, run(reducers::sum(), source.clone()));
+        assert_eq!(6480, run(reducers::product(), source.clone()));
+        assert_eq!(Some(1), run(reducers::min(), source.clone()));
+        assert_eq!(Some(9), run(reducers::max(), source.clone()));
+        assert_eq!(Some(3), run(reducers::first(), source.clone()));
+        assert_eq!(Some(6), run(reducers::last(), source.clone()));
+        assert!(run(reducers::any(|x: &isize| *x > 8), source.clone()));
+        assert!(!run(reducers::all(|x: &isize| *x > 1), source.clone()));
+        assert!(run(reducers::all(|x: &isize| *x > 0), source.clone()));
+
+        assert_eq!(0, run(reducers::count(), Vec::new()));
+        assert_eq!(0, run(reducers::sum(), Vec::new()));
+        assert_eq!(1, run(reducers::product(), Vec::new()));
+        assert_eq!(None, run(reducers::max(), Vec::new()));
+        assert_eq!(None, run(reducers::first(), Vec::new()));
+        assert!(!run(reducers::any(|_: &isize| true), Vec::new()));
+        assert!(run(reducers::all(|_: &isize| false), Vec::new()));
+
+        let words = vec!["bb", "a", "cc", "ddd", "e"];
+        let by_length = |a: &&str, b: &&str| a.len().cmp(&b.len());
+        let result:Result<_, ()> = super::transduce(transducers::map(|x| x), reducers::min_by(by_length), words.clone());
+        assert_eq!(Ok(Some("a")), result);
+        let result:Result<_, ()> = super::transduce(transducers::take(4), reducers::max_by(by_length), words);
+        assert_eq!(Ok(Some("ddd")), result);
+
+        let result:Result<_, ()> = super::transduce(transducers::filter(|x: &i32| x % 2 == 0),
+                                                    reducers::mean(),
+                                                    vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(Ok(Some(4.0)), result);
+        let result:Result<Option<f64>, ()> = super::transduce(transducers::map(|x: f32| x), reducers::mean(), vec![]);
+        assert_eq!(Ok(None), result);
+        let result:Result<_, ()> = super::transduce(transducers::map(|x: u64| x), reducers::mean(), vec![1u64 << 40, 3 << 40]);
+        assert_eq!(Ok(Some((1u64 << 41) as f64)), result);
+    }
+
+    #[test]
+    fn test_aggregates_stop_early() {
+        let mut seen = Vec::new();
+        let result:Result<_, ()> = super::transduce(transducers::map(|x: usize| x * 2),
+                                                    reducers::any(|x: &usize| *x > 4),
+                                                    (1..).inspect(|x| seen.push(*x)));
+        assert_eq!(Ok(true), result);
+        assert_eq!(vec![1, 2, 3], seen);
+
+        let result:Result<_, ()> = super::transduce(transducers::filter(|x: &usize| *x > 6),
+                                                    reducers::first(),
+                                                    1..);
+        assert_eq!(Ok(Some(7)), result);
+
+        let log = vec![("a", 500), ("b", 200), ("a", 404), ("b", 503)];
+        let result:Result<_, ()> = super::transduce(transducers::map(|x| x),
+                                                    reducers::group_by_into(|&(host, _): &(&str, usize)| host,
+                                                                            reducers::first()),
+                                                    log);
+        let mut expected = HashMap::new();
+        expected.insert("a", Some(("a", 500)));
+        expected.insert("b", Some(("b", 200)));
+        assert_eq!(Ok(expected), result);
+    }
+
+    #[test]
+    fn test_moments() {
+        fn close(expected: f64, actual: Option<f64>) -> bool {
+            (expected - actual.unwrap()).abs() < 1e-9
+        }
+
+        let source = vec![2, 4, 4, 4, 5, 5, 7, 9];
+        let result:Result<_, ()> = super::transduce(transducers::map(|x| x), reducers::moments(), source.clone());
+        let moments = result.unwrap();
+        assert_eq!(8, moments.count());
+        assert!(close(5.0, moments.mean()));
+        assert!(close(4.0, moments.population_variance()));
+        assert!(close(32.0 / 7.0, moments.variance()));
+        assert!(close(0.65625, moments.skewness()));
+
+        let result:Result<_, ()> = super::transduce(transducers::map(|x| x), reducers::std_dev(), source);
+        assert!(close((32.0f64 / 7.0).sqrt(), result.unwrap()));
+
+        let result:Result<_, ()> = super::transduce(transducers::map(|x: u64| x), reducers::variance(), vec![2u64, 4, 6]);
+        assert!(close(4.0, result.unwrap()));
+
+        let offset = vec![4.0, 7.0, 13.0, 16.0];
+        let result:Result<_, ()> = super::transduce(transducers::map(|x: f64| x + 1e9), reducers::variance(), offset);
+        assert!(close(30.0, result.unwrap()));
+
+        let result:Result<_, ()> = super::transduce(transducers::map(|x: f64| x), reducers::variance(), vec![1.0]);
+        assert_eq!(Ok(None), result);
+        let result:Result<_, ()> = super::transduce(transducers::map(|x: f64| x), reducers::skewness(), vec![3.0, 3.0]);
+        assert_eq!(Ok(None), result);
+    }
+
+    #[test]
+    fn test_quantiles() {
+        let result:Result<_, ()> = super::transduce(transducers::map(|x: u32| x), reducers::quantile(0.5), vec![3, 1, 2]);
+        assert_eq!(Ok(Some(2.0)), result);
+        let result:Result<_, ()> = super::transduce(transducers::map(|x: u32| x), reducers::quantile(0.5), vec![]);
+        assert_eq!(Ok(None), result);
+        let result:Result<_, ()> = super::transduce(transducers::map(|x: u64| x), reducers::quantile(0.5), vec![3u64, 1, 2]);
+        assert_eq!(Ok(Some(2.0)), result);
+        let result:Result<_, ()> = super::transduce(transducers::map(|x: usize| x), reducers::quantiles(&[0.0, 1.0]), 1..6);
+        assert_eq!(Ok(Some(vec![1.0, 5.0])), result);
+
+        for &(p, five, six) in &[(0.0, 1.0, 1.0), (1.0, 5.0, 6.0), (0.99, 4.96, 5.95)] {
+            let result:Result<_, ()> = super::transduce(transducers::map(|x: u32| x), reducers::quantile(p), 1..6);
+            let estimate = result.unwrap().unwrap();
+            assert!((estimate - five).abs() < 1e-9, "p{} of 1..=5 was {}", p, estimate);
+            let result:Result<_, ()> = super::transduce(transducers::map(|x: u32| x), reducers::quantile(p), 1..7);
+            let estimate = result.unwrap().unwrap();
+            assert!((estimate - six).abs() < 1e-9, "p{} of 1..=6 was {}", p, estimate);
+        }
+
+        let result:Result<_, ()> = super::transduce(transducers::map(|x: f64| x),
+                                                    reducers::quantiles(&[0.0, 0.5, 1.0]),
+                                                    vec![3.0, f64::NAN, 1.0, 2.0, 5.0, f64::NAN, 4.0, 6.0, 7.0]);
+        assert_eq!(Ok(Some(vec![1.0, 4.0, 7.0])), result);
+        let result:Result<_, ()> = super::transduce(transducers::map(|x: f64| x), reducers::quantile(0.5), vec![f64::NAN]);
+        assert_eq!(Ok(None), result);
+
+        let (mut tx, rx) = transducing_channel(transducers::map(|micros: u32| micros as f64 / 1000.0));
+        thread::spawn(move|| {
+            for i in 0..10000 {
+                tx.send((i * 7919) % 10000 + 1).unwrap();
+            }
+            tx.close().unwrap();
+        });
+        let result:Result<_, ()> = super::transduce(transducers::map(|x| x), reducers::quantiles(&[0.5, 0.99]), rx.iter());
+        let latencies = result.unwrap().unwrap();
+        assert!((latencies[0] - 5.0).abs() < 0.05, "p50 {}", latencies[0]);
+        assert!((latencies[1] - 9.9).abs() < 0.05, "p99 {}", latencies[1]);
+    }
+
+    #[test]
+    fn test_frequencies() {
+        let text = vec!["the cat sat", "on the mat", "the end"];
+        let transducer = transducers::mapcat(|line: &str| line.split(' ').collect::<Vec<_>>());
+        let result:Result<_, ()> = super::transduce(transducer, reducers::frequencies(), text);
+        let counts = result.unwrap();
+        assert_eq!(3, counts["the"]);
+        assert_eq!(1, counts["mat"]);
+        assert_eq!(6, counts.len());
+
+        let result:Result<_, ()> = super::transduce(transducers::map(|x| x),
+                                                    reducers::top_k(2, |&(_, n): &(&str, usize)| n),
+                                                    vec![("a", 3), ("b", 7), ("c", 5), ("d", 7), ("e", 1)]);
+        assert_eq!(Ok(vec![("b", 7), ("d", 7)]), result);
+
+        let result:Result<_, ()> = super::transduce(transducers::filter(|x: &isize| *x < 0),
+                                                    reducers::top_k(3, |x: &isize| x.abs()),
+                                                    vec![-1, 5, -8, -2, 9, -8, -3]);
+        assert_eq!(Ok(vec![-8, -8, -3]), result);
+
+        let result:Result<_, ()> = super::transduce(transducers::map(|x| x), reducers::top_k(0, |x: &isize| *x), vec![1, 2]);
+        assert_eq!(Ok(vec![]), result);
+    }
+
+    #[test]
+    fn test_group_by() {
+        let source = vec!["apple", "bean", "avocado", "banana", "cherry"];
+        let result:Result<_, ()> = super::transduce(transducers::map(|x: &str| x.to_uppercase()),
+                                                    reducers::group_by(|x: &String| x.chars().next()),
+                                                    source);
+        let mut expected = HashMap::new();
+        expected.insert(Some('A'), vec!["APPLE".to_string(), "AVOCADO".to_string()]);
+        expected.insert(Some('B'), vec!["BEAN".to_string(), "BANANA".to_string()]);
+        expected.insert(Some('C'), vec!["CHERRY".to_string()]);
+        assert_eq!(Ok(expected), result);
+
+        let log = vec![("a", 500), ("b", 200), ("a", 404), ("c", 200), ("b", 503), ("a", 500)];
+        let errors_per_host = reducers::group_by_into(|&(host, _): &(&str, usize)| host,
+                                                      reducers::fold(0, |n, _| n + 1));
+        let result:Result<_, ()> = super::transduce(transducers::filter(|&(_, status): &(&str, usize)| status >= 500),
+                                                    errors_per_host,
+                                                    log.clone());
+        let mut expected = HashMap::new();
+        expected.insert("a", 2);
+        expected.insert("b", 1);
+        assert_eq!(Ok(expected), result);
+
+        let by_host_and_status = reducers::group_by_into(|&(host, _): &(&str, usize)| host,
+                                                         reducers::group_by(|&(_, status): &(&str, usize)| status));
+        let result:Result<_, ()> = super::transduce(transducers::filter(|&(host, _): &(&str, usize)| host != "c"),
+                                                    by_host_and_status,
+                                                    log);
+        let result = result.unwrap();
+        assert_eq!(2, result.len());
+        assert_eq!(vec![("a", 500), ("a", 500)], result["a"][&500]);
+        assert_eq!(vec![("a", 404)], result["a"][&404]);
+        assert_eq!(vec![("b", 200)], result["b"][&200]);
+    }
+
+    #[test]
+    fn test_try_iterator() {
+        let source = vec![1, 2, 3];
+        let transducer = transducers::mapcat(duplicator);
+        let result:Result<Vec<isize>, String> = source.into_iter().try_transduce(transducer).collect();
+        assert_eq!(Ok(vec![1, 1, 2, 2, 3, 3]), result);
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum IngestError {
+        BadRecord(String),
+        Negative(isize)
+    }
+
+    impl From<ParseIntError> for IngestError {
+        fn from(e: ParseIntError) -> IngestError {
+            IngestError::BadRecord(e.to_string())
+        }
+    }
+
+    #[test]
+    fn test_try_transducers() {
+        let parse = transducers::try_map(|x: &str| x.parse::<isize>());
+        let positive = transducers::try_filter(|x: &isize| {
+            if *x < 0 {
+                Err(IngestError::Negative(*x))
+            } else {
+                Ok(*x > 0)
+            }
+        });
+        let transducer = super::compose(positive, parse);
+
+        let result:Result<_, IngestError> = super::transduce(&transducer,
+                                                             reducers::fold(0, |acc, x| acc + x),
+                                                             vec!["1", "0", "2"]);
+        assert_eq!(Ok(3), result);
+
+        let result:Result<_, IngestError> = super::transduce(&transducer,
+                                                             reducers::fold(0, |acc, x| acc + x),
+                                                             vec!["1", "-2", "x"]);
+        assert_eq!(Err(IngestError::Negative(-2)), result);
+
+        let result:Result<_, IngestError> = vec!["1", "0", "2"].try_transduce_into(&transducer);
+        assert_eq!(Ok(vec![1, 2]), result);
+
+        let source = vec!["3", "x", "-1"];
+        let result:Result<_, IngestError> = source.try_transduce_ref(
+            transducers::map(|x: &&str| *x).try_map(|x: &str| x.parse::<isize>()));
+        assert_eq!(Err(IngestError::BadRecord("invalid digit found in string".to_owned())), result);
+
+        let result:Result<BTreeSet<_>, IngestError> = vec!["2", "1", "2"].try_transduce_collect(&transducer);
+        assert_eq!(Ok(vec![1, 2].into_iter().collect()), result);
+
+        let result:Vec<Result<isize, IngestError>> = vec!["1", "2", "x", "3"].into_iter()
+            .try_transduce(transducer)
+            .collect();
+        assert_eq!(vec![Ok(1),
+                        Ok(2),
+                        Err(IngestError::BadRecord("invalid digit found in string".to_owned()))],
+                   result);
+
+        let transducer = transducers::try_keep(|x: &str| {
+            if x.is_empty() {
+                Ok(None)
+            } else {
+                x.parse::<isize>().map(Some)
+            }
+        });
+        let result:Result<_, IngestError> = super::transduce(transducer,
+                                                             reducers::fold(0, |acc, x| acc + x),
+                                                             vec!["1", "", "2"]);
+        assert_eq!(Ok(3), result);
+    }
+
+    #[test]
+    fn test_chaining() {
+        let source = vec![1, 2, 3, 4, 5, 6, 7];
+        let transducer = transducers::map(|x| x + 1)
+            .filter(|x| x % 2 == 0)
+            .take(2);
+        let result = source.transduce_into(transducer).unwrap();
+        assert_eq!(vec![2, 4], result);
+
+        let source = vec![1, 2, 3, 4, 5, 6, 7];
+        let transducer = comp!(transducers::map(|x| x + 1),
+                               transducers::filter(|x| x % 2 == 0),
+                               transducers::partition_all(2),
+                               transducers::take(1));
+        let result = source.transduce_into(transducer).unwrap();
+        assert_eq!(vec![vec![2, 4]], result);
+
+        let source = vec![1, 2, 3];
+        let result = source.transduce_into(comp!(transducers::drop(1))).unwrap();
+        assert_eq!(vec![2, 3], result);
+    }
+
+    fn stage(name: &str) -> BoxedTransducer<'static, isize, isize, Vec<isize>, ()> {
+        match name {
+            "double" => transducers::map(|x| x * 2).boxed(),
+            "evens" => transducers::filter(|x: &isize| x % 2 == 0).boxed(),
+            "first_three" => transducers::take(3).boxed(),
+            "sums" => transducers::partition_all(2).map(|x: Vec<isize>| x.iter().sum()).boxed(),
+            _ => panic!("Unknown stage")
+        }
+    }
+
+    #[test]
+    fn test_boxed() {
+        let stages:Vec<_> = vec!["evens", "double", "first_three"].into_iter().map(stage).collect();
+        let transducer = boxed::chain(stages);
+        let result = vec![1, 2, 3, 4, 5, 6, 7, 8].transduce_into(&transducer).unwrap();
+        assert_eq!(vec![4, 8, 12], result);
+        let result = vec![10, 11, 12].transduce_into(&transducer).unwrap();
+        assert_eq!(vec![20, 24], result);
+
+        let transducer = stage("sums").map(|x| x + 1).boxed();
+        let transducer = boxed::chain(vec![stage("double"), transducer]);
+        let result = vec![1, 2, 3].transduce_into(transducer).unwrap();
+        assert_eq!(vec![7, 7], result);
+
+        let result = vec![1, 2, 3].transduce_into(boxed::chain(vec![])).unwrap();
+        assert_eq!(vec![1, 2, 3], result);
+    }
+
+    #[test]
+    fn test_collect() {
+        let source = vec!["a", "bb", "a", "ccc"];
+        let result:HashMap<&str, usize> = source.transduce_collect(transducers::map(|x: &'static str| (x, x.len()))).unwrap();
+        assert_eq!(3, result.len());
+        assert_eq!(3, result["ccc"]);
+
+        let result:BTreeSet<usize> = (1..10).transduce_collect(transducers::map(|x: usize| x % 3)).unwrap();
+        assert_eq!(vec![0, 1, 2], result.into_iter().collect::<Vec<_>>());
+
+        let result:String = "hello world".chars()
+            .transduce_collect(transducers::remove(|c: &char| "aeiou".contains(*c)))
+            .unwrap();
+        assert_eq!("hll wrld", result);
+    }
+
     #[test]
     fn test_filter() {
         {
@@ -137,6 +908,107 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_early_termination() {
+        // (into [] (comp (take 5) (partition-all 2)) (range 10))
+        let result = (0..10).collect::<Vec<usize>>()
+            .transduce_into(comp!(transducers::take(5), transducers::partition_all(2)))
+            .unwrap();
+        assert_eq!(vec![vec![0, 1], vec![2, 3], vec![4]], result);
+
+        // (into [] (comp (partition-all 2) (take 2)) (range 10))
+        let result = (0..10).collect::<Vec<usize>>()
+            .transduce_into(comp!(transducers::partition_all(2), transducers::take(2)))
+            .unwrap();
+        assert_eq!(vec![vec![0, 1], vec![2, 3]], result);
+
+        // (into [] (comp (partition-all 3) (take 1)) (range 2))
+        let result = vec![0, 1]
+            .transduce_into(comp!(transducers::partition_all(3), transducers::take(1)))
+            .unwrap();
+        assert_eq!(vec![vec![0, 1]], result);
+
+        // (into [] (comp (partition-all 2) (take 1)) [0 1 2])
+        let result = vec![0, 1, 2]
+            .transduce_into(comp!(transducers::partition_all(2), transducers::take(1)))
+            .unwrap();
+        assert_eq!(vec![vec![0, 1]], result);
+
+        // (into [] (comp (partition-by odd?) (take-while #(< (count %) 2))) [1 2 2 3])
+        let result = vec![1, 2, 2, 3]
+            .transduce_into(comp!(transducers::partition_by(|x: &usize| x % 2 == 1),
+                                  transducers::take_while(|x: &Vec<usize>| x.len() < 2)))
+            .unwrap();
+        assert_eq!(vec![vec![1]], result);
+
+        // (into [] (comp (take 5) (partition 2)) (range 10))
+        let result = (0..10).collect::<Vec<usize>>()
+            .transduce_into(comp!(transducers::take(5), transducers::partition(2)))
+            .unwrap();
+        assert_eq!(vec![vec![0, 1], vec![2, 3]], result);
+
+        // (into [] (comp (mapcat #(repeat 3 %)) (take 4) (partition-all 3)) [1 2])
+        let transducer = comp!(transducers::mapcat(|x| vec![x; 3]),
+                               transducers::take(4),
+                               transducers::partition_all(3));
+        let result:Vec<Vec<usize>> = vec![1, 2].into_iter().transduce(&transducer).collect();
+        assert_eq!(vec![vec![1, 1, 1], vec![2]], result);
+        let (mut tx, rx) = transducing_channel(transducer);
+        assert_eq!(Ok(true), tx.send(1));
+        assert_eq!(Ok(false), tx.send(2));
+        assert_eq!(Ok(false), tx.send(3));
+        tx.close().unwrap();
+        assert_eq!(vec![vec![1, 1, 1], vec![2]], rx.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_partition_all_exact() {
+        // (into [] (partition-all 2) [1 2 3 4])
+        let result = vec![1, 2, 3, 4].transduce_into(transducers::partition_all(2)).unwrap();
+        let expected_result:Vec<Vec<usize>> = vec![vec![1, 2], vec![3, 4]];
+        assert_eq!(expected_result, result);
+
+        let result = Vec::<usize>::new().transduce_into(transducers::partition_all(2)).unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_window() {
+        let source = vec![1, 2, 3, 4, 5];
+        let result = source.transduce_ref(transducers::window(3, 1)).unwrap();
+        let expected_result:Vec<Vec<&usize>> = vec![vec![&1, &2, &3], vec![&2, &3, &4], vec![&3, &4, &5]];
+        assert_eq!(expected_result, result);
+
+        let result = source.transduce_ref(transducers::window(2, 3)).unwrap();
+        let expected_result:Vec<Vec<&usize>> = vec![vec![&1, &2], vec![&4, &5]];
+        assert_eq!(expected_result, result);
+
+        let result = source.transduce_ref(transducers::window(2, 2)).unwrap();
+        let expected_result:Vec<Vec<&usize>> = vec![vec![&1, &2], vec![&3, &4]];
+        assert_eq!(expected_result, result);
+
+        let readings = vec![1.0, 2.0, 6.0, 3.0];
+        let transducer = transducers::window(2, 1)
+            .map(|w: Vec<f64>| w.iter().sum::<f64>() / w.len() as f64);
+        assert_eq!(vec![1.5, 4.0, 4.5], readings.transduce_into(transducer).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "window size must be greater than zero")]
+    fn test_window_zero_size() {
+        transducers::window::<usize>(0, 1);
+    }
+
+    #[test]
+    fn test_pairwise() {
+        let source = vec![1, 4, 9, 16];
+        let transducer = transducers::pairwise().map(|(a, b)| b - a);
+        assert_eq!(vec![3, 5, 7], source.transduce_into(transducer).unwrap());
+
+        let result: Vec<(usize, usize)> = vec![1].transduce_into(transducers::pairwise()).unwrap();
+        assert!(result.is_empty());
+    }
+
     #[test]
     fn test_take() {
         let source = vec![1, 2, 3, 4, 5, 6, 7];
@@ -193,6 +1065,12 @@ mod test {
         let result = source.transduce_into(transducer).unwrap();
         let expected_result:Vec<Vec<usize>> = vec![vec![1, 2, 3], vec![4, 5, 6]];
         assert_eq!(expected_result, result);
+
+        let source = vec![1, 2, 2, 3, 5, 4];
+        let transducer = transducers::partition_by(|x: &usize| x % 2 == 1);
+        let result = source.transduce_into(transducer).unwrap();
+        let expected_result:Vec<Vec<usize>> = vec![vec![1], vec![2, 2], vec![3, 5], vec![4]];
+        assert_eq!(expected_result, result);
     }
 
     #[test]
@@ -234,6 +1112,22 @@ mod test {
         assert_eq!(expected_result, result);
     }
 
+    #[test]
+    fn test_scan() {
+        let source = vec![1, 2, 3, 4];
+        let transducer = transducers::scan(0, |total, x| total + x);
+        let result = source.transduce_into(transducer).unwrap();
+        assert_eq!(vec![1, 3, 6, 10], result);
+
+        let transducer = transducers::scan(0, |max: &usize, x: usize| *max.max(&x));
+        let (mut tx, rx) = transducing_channel(transducer);
+        for x in [3, 1, 4, 1, 5] {
+            tx.send(x).unwrap();
+        }
+        tx.close().unwrap();
+        assert_eq!(vec![3, 3, 4, 4, 5], rx.iter().collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_interpose() {
         let source = vec![1, 2, 3, 4];
@@ -252,6 +1146,171 @@ mod test {
         assert_eq!(expected_result, result);
     }
 
+    #[test]
+    fn test_distinct() {
+        let source = vec![1, 2, 2, 2, 3, 3, 2, 3, 4, 1];
+        let result = source.transduce_into(transducers::distinct()).unwrap();
+        assert_eq!(vec![1, 2, 3, 4], result);
+
+        let source = vec!["apple", "avocado", "banana", "cherry", "blueberry"];
+        let transducer = transducers::distinct_by(|x: &&str| x.chars().next());
+        let result = source.transduce_into(transducer).unwrap();
+        assert_eq!(vec!["apple", "banana", "cherry"], result);
+
+        let transducer = transducers::distinct_by(|x: &(usize, &str)| x.0);
+        let (mut tx, rx) = transducing_channel(transducer);
+        thread::spawn(move|| {
+            for event in [(1, "a"), (2, "b"), (1, "c"), (3, "d"), (2, "e")] {
+                tx.send(event).unwrap();
+            }
+            tx.close().unwrap();
+        });
+        assert_eq!(vec![(1, "a"), (2, "b"), (3, "d")], rx.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_distinct_bounded() {
+        let source = vec![1, 2, 1, 3, 2, 4, 1, 2, 1];
+        let result = source.transduce_into(transducers::distinct_bounded(2)).unwrap();
+        assert_eq!(vec![1, 2, 3, 2, 4, 1, 2], result);
+
+        let result = vec![1, 2, 1, 3, 1, 2].transduce_into(transducers::distinct_bounded(0)).unwrap();
+        assert_eq!(vec![1, 2, 1, 3, 1, 2], result);
+
+        let transducer = transducers::distinct_within(2);
+        let (mut tx, rx) = transducing_channel(transducer);
+        thread::spawn(move|| {
+            for event in [1, 1, 2, 1, 3, 3, 4, 1, 1] {
+                tx.send(event).unwrap();
+            }
+            tx.close().unwrap();
+        });
+        assert_eq!(vec![1, 2, 3, 4, 1], rx.iter().collect::<Vec<_>>());
+
+        let source = vec![1, 1, 2, 2, 1, 3];
+        let result = source.transduce_into(transducers::distinct_within(1)).unwrap();
+        assert_eq!(vec![1, 2, 1, 3], result);
+    }
+
+    #[test]
+    fn test_stateful() {
+        let interpose = transducers::stateful(false, |started: &mut bool, x: usize, out: &mut Emitter<usize>| {
+            if *started {
+                out.emit(0);
+            }
+            *started = true;
+            out.emit(x);
+        }, |_, _| ());
+        let result = vec![1, 2, 3].transduce_into(interpose).unwrap();
+        assert_eq!(vec![1, 0, 2, 0, 3], result);
+
+        let pairs = transducers::stateful(Vec::new(), |held: &mut Vec<usize>, x, out| {
+            held.push(x);
+            if held.len() == 2 {
+                out.emit(mem::take(held));
+            }
+        }, |held, out| {
+            if !held.is_empty() {
+                out.emit(mem::take(held));
+            }
+        });
+        let result = vec![1, 2, 3, 4, 5].transduce_into(pairs.clone()).unwrap();
+        assert_eq!(vec![vec![1, 2], vec![3, 4], vec![5]], result);
+
+        let until_zero = transducers::stateful((), |_: &mut (), x: usize, out: &mut Emitter<usize>| {
+            if x == 0 {
+                out.stop();
+            } else {
+                out.emit(x);
+            }
+        }, |_, _| ());
+        let transducer = until_zero.then(pairs);
+        let result = vec![1, 2, 3, 0, 4, 5].transduce_into(transducer).unwrap();
+        assert_eq!(vec![vec![1, 2], vec![3]], result);
+
+        let transducer = transducers::stateful(0, |n: &mut usize, x: usize, out: &mut Emitter<usize>| {
+            *n += 1;
+            out.emit(x);
+            out.emit(x);
+        }, |n, out| out.emit(*n)).take(3);
+        let result = vec![7, 8, 9].transduce_into(transducer).unwrap();
+        assert_eq!(vec![7, 7, 8], result);
+
+        let transducer = transducers::stateful((), |_: &mut (), x: usize, out: &mut Emitter<usize>| out.emit(x),
+                                               |_, out| out.emit(0))
+            .take_while(|x| *x < 3);
+        let result = vec![1, 2, 3, 4].transduce_into(&transducer).unwrap();
+        assert_eq!(vec![1, 2], result);
+        let result = vec![1, 2].transduce_into(&transducer).unwrap();
+        assert_eq!(vec![1, 2, 0], result);
+    }
+
+    transducer! {
+        #[derive(Clone)]
+        pub struct ClampTransducer<T> { min: T, max: T }
+
+        pub struct ClampReducer { clamped: usize = 0 }
+
+        impl Reducing for ClampReducer
+            where T: PartialOrd + Clone {
+
+            type Item = T;
+
+            fn step(&mut self, value: T) -> Result<StepResult, E> {
+                if value < self.t.min {
+                    self.clamped += 1;
+                    self.rf.step(self.t.min.clone())
+                } else if value > self.t.max {
+                    self.clamped += 1;
+                    self.rf.step(self.t.max.clone())
+                } else {
+                    self.rf.step(value)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_transducer_macro() {
+        let source = vec![-5, 0, 5, 10, 15];
+        let transducer = ClampTransducer { min: 0, max: 10 };
+        let result = source.clone().transduce_into(transducer.clone()).unwrap();
+        assert_eq!(vec![0, 0, 5, 10, 10], result);
+
+        let transducer = transducers::map(|x: isize| x * 2).then(transducer).take(3);
+        let result = source.transduce_into(transducer).unwrap();
+        assert_eq!(vec![0, 0, 10], result);
+    }
+
+    #[test]
+    fn test_random_sample() {
+        let source:Vec<usize> = (0..10000).collect();
+        let transducer = transducers::random_sample_with(0.25, SplitMix64::seed(42));
+        let result = source.transduce_ref(&transducer).unwrap();
+        assert!(result.len() > 2300 && result.len() < 2700);
+        assert_eq!(result, source.transduce_ref(&transducer).unwrap());
+
+        let other = source.transduce_ref(transducers::random_sample_with(0.25, SplitMix64::seed(43))).unwrap();
+        assert!(result != other);
+
+        let all = source.transduce_ref(transducers::random_sample(1.0)).unwrap();
+        assert_eq!(10000, all.len());
+        let none = source.transduce_ref(transducers::random_sample(0.0)).unwrap();
+        assert!(none.is_empty());
+
+        let transducer = transducers::random_sample(0.25);
+        let first = source.transduce_ref(&transducer).unwrap();
+        let second = source.transduce_ref(&transducer).unwrap();
+        assert!(first.len() > 2300 && first.len() < 2700);
+        assert!(first != second);
+
+        let mut rng = SplitMix64::seed(42);
+        let first = source.transduce_ref(transducers::random_sample_with(0.25, &mut rng)).unwrap();
+        let second = source.transduce_ref(transducers::random_sample_with(0.25, &mut rng)).unwrap();
+        assert_eq!(result, first);
+        assert!(first != second);
+    }
+
     #[test]
     fn test_channels() {
         let transducer = transducers::map(|x| x + 1);
@@ -266,4 +1325,271 @@ mod test {
         assert_eq!(2, rx.recv().unwrap());
         assert_eq!(3, rx.recv().unwrap());
     }
+
+    #[test]
+    fn test_sync_channel() {
+        use std::sync::mpsc::TrySendError;
+
+        let (mut tx, rx): (TransducingSyncSender<_, _, (), _>, _) =
+            transducing_sync_channel(1, transducers::mapcat(|x: usize| vec![x; 3]));
+        assert_eq!(Ok(true), tx.try_send(1));
+        assert_eq!(2, tx.pending());
+        assert_eq!(Err(TransducingError::Channel(TrySendError::Full(2))), tx.try_send(2));
+        assert_eq!(1, rx.recv().unwrap());
+        assert_eq!(Err(TransducingError::Channel(TrySendError::Full(2))), tx.try_send(2));
+        assert_eq!(1, tx.pending());
+        let handle = thread::spawn(move|| {
+            tx.send(2).unwrap();
+            tx.send(3).unwrap();
+            tx.close().unwrap();
+        });
+        assert_eq!(vec![1, 1, 2, 2, 2, 3, 3, 3], rx.iter().collect::<Vec<_>>());
+        handle.join().unwrap();
+
+        let (mut tx, rx): (TransducingSyncSender<_, _, (), _>, _) =
+            transducing_sync_channel(4, transducers::partition_all(3).take(2));
+        for i in 0..4 {
+            assert_eq!(Ok(true), tx.try_send(i));
+        }
+        assert_eq!(Ok(true), tx.try_send(4));
+        assert_eq!(Ok(false), tx.try_send(5));
+        assert_eq!(Ok(false), tx.send(6));
+        tx.close().unwrap();
+        assert_eq!(vec![vec![0, 1, 2], vec![3, 4, 5]], rx.iter().collect::<Vec<_>>());
+
+        let (mut tx, rx): (TransducingSyncSender<_, _, (), _>, _) =
+            transducing_sync_channel(4, transducers::partition_all(2));
+        assert_eq!(Ok(true), tx.send(1));
+        drop(tx);
+        assert_eq!(vec![vec![1]], rx.iter().collect::<Vec<_>>());
+
+        let (mut tx, rx): (TransducingSyncSender<_, _, (), _>, _) =
+            transducing_sync_channel(1, transducers::map(|x: usize| x));
+        drop(rx);
+        assert_eq!(Ok(true), tx.try_send(1));
+        assert_eq!(Err(TransducingError::Channel(TrySendError::Disconnected(2))), tx.try_send(2));
+        assert!(tx.close().is_err());
+
+        let (mut tx, rx): (TransducingSyncSender<_, _, ParseIntError, _>, _) =
+            transducing_sync_channel(4, transducers::try_map(|x: &str| x.parse::<usize>()));
+        assert_eq!(Ok(true), tx.send("1"));
+        match tx.try_send("x") {
+            Err(TransducingError::Reducing(e)) => assert_eq!("x".parse::<usize>().unwrap_err(), e),
+            other => panic!("expected a reducing error, got {:?}", other)
+        }
+        tx.close().unwrap();
+        assert_eq!(vec![1], rx.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_shared_sender() {
+        let (tx, rx) = shared_transducing_channel(transducers::partition_all(3));
+        let handles:Vec<_> = (0..4).map(|t| {
+            let tx = tx.clone();
+            thread::spawn(move|| {
+                for i in 0..10 {
+                    tx.send(t * 10 + i).unwrap();
+                }
+            })
+        }).collect();
+        drop(tx);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let partitions = rx.iter().collect::<Vec<Vec<usize>>>();
+        assert_eq!(14, partitions.len());
+        assert!(partitions[..13].iter().all(|p| p.len() == 3));
+        let mut values = partitions.concat();
+        values.sort();
+        assert_eq!((0..40).collect::<Vec<_>>(), values);
+
+        let (tx, rx) = shared_transducing_channel(transducers::dedupe().take(3));
+        let other = tx.clone();
+        assert_eq!(Ok(true), tx.send(1));
+        assert_eq!(Ok(true), other.send(1));
+        assert_eq!(Ok(true), other.send(2));
+        assert_eq!(Ok(false), tx.send(3));
+        assert_eq!(Ok(false), other.send(4));
+        assert_eq!(Ok(()), tx.close());
+        assert_eq!(vec![1, 2, 3], rx.try_iter().collect::<Vec<_>>());
+        assert_eq!(Ok(()), other.close());
+        assert!(rx.recv().is_err());
+
+        let (tx, rx) = shared_transducing_channel(transducers::map(|x: usize| {
+            assert!(x != 0, "bad value");
+            x
+        }).partition_all(2));
+        let other = tx.clone();
+        tx.send(1).unwrap();
+        assert!(thread::spawn(move|| other.send(0)).join().is_err());
+        tx.send(2).unwrap();
+        tx.send(3).unwrap();
+        assert_eq!(Ok(()), tx.close());
+        assert_eq!(vec![vec![1, 2], vec![3]], rx.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_fan_in_channel() {
+        let (factory, rx) = fan_in_channel(transducers::partition_all(4));
+        let handles:Vec<_> = (0..3).map(|t| {
+            let mut tx = factory.sender();
+            thread::spawn(move|| {
+                for i in 0..6 {
+                    tx.send(t * 10 + i).unwrap();
+                }
+                tx.close().unwrap();
+            })
+        }).collect();
+        drop(factory);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let mut partitions = rx.iter().collect::<Vec<Vec<usize>>>();
+        partitions.sort();
+        assert_eq!(vec![vec![0, 1, 2, 3], vec![4, 5],
+                        vec![10, 11, 12, 13], vec![14, 15],
+                        vec![20, 21, 22, 23], vec![24, 25]], partitions);
+
+        let (factory, rx) = fan_in_channel(transducers::take(1));
+        let mut first = factory.sender();
+        let mut second = factory.clone().sender();
+        assert_eq!(Ok(false), first.send(1));
+        assert_eq!(Ok(false), first.send(2));
+        assert_eq!(Ok(false), second.send(3));
+        assert_eq!(vec![1, 3], rx.try_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_sender_completion() {
+        use std::sync::mpsc::{SendError, TrySendError};
+
+        let (mut tx, rx) = transducing_channel(transducers::partition_all(2));
+        let completion = tx.completion();
+        let handle = thread::spawn(move|| {
+            for i in 0..3 {
+                tx.send(i).unwrap();
+            }
+            panic!("producer failed");
+        });
+        assert!(handle.join().is_err());
+        assert_eq!(Ok(()), completion.wait());
+        assert_eq!(vec![vec![0, 1], vec![2]], rx.iter().collect::<Vec<_>>());
+
+        let (mut tx, rx) = transducing_channel(transducers::partition_by(|x: &usize| x / 10));
+        let completion = tx.completion();
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        assert!(!completion.is_complete());
+        tx.close().unwrap();
+        assert!(completion.is_complete());
+        assert_eq!(vec![vec![1, 2]], rx.iter().collect::<Vec<_>>());
+
+        let (mut tx, rx) = transducing_channel(transducers::partition_all(2));
+        let completion = tx.completion();
+        tx.send(1).unwrap();
+        drop(rx);
+        drop(tx);
+        assert_eq!(Err(SendError(())), completion.wait());
+
+        let (mut tx, rx): (TransducingSyncSender<_, _, (), _>, _) =
+            transducing_sync_channel(2, transducers::partition_all(2));
+        let completion = tx.completion();
+        let handle = thread::spawn(move|| {
+            for i in 0..3 {
+                tx.send(i).unwrap();
+            }
+            panic!("producer failed");
+        });
+        assert_eq!(vec![vec![0, 1], vec![2]], rx.iter().collect::<Vec<_>>());
+        assert!(handle.join().is_err());
+        assert_eq!(Ok(()), completion.wait());
+
+        let (mut tx, rx): (TransducingSyncSender<_, _, (), _>, _) =
+            transducing_sync_channel(1, transducers::partition_all(2));
+        let completion = tx.completion();
+        tx.send(1).unwrap();
+        drop(rx);
+        drop(tx);
+        assert_eq!(Err(TransducingError::Channel(TrySendError::Disconnected(()))), completion.wait());
+
+        let (mut tx, rx): (TransducingSyncSender<_, _, (), _>, _) =
+            transducing_sync_channel(1, transducers::partition_all(2));
+        let completion = tx.completion();
+        for i in 0..3 {
+            tx.send(i).unwrap();
+        }
+        drop(tx);
+        assert_eq!(Err(TransducingError::Channel(TrySendError::Full(()))), completion.wait());
+        assert_eq!(vec![vec![0, 1]], rx.iter().collect::<Vec<_>>());
+
+        let (mut tx, rx): (TransducingSyncSender<_, _, (), _>, _) =
+            transducing_sync_channel(1, transducers::partition_all(2));
+        let completion = tx.completion();
+        let handle = thread::spawn(move|| {
+            for i in 0..3 {
+                tx.send(i).unwrap();
+            }
+            panic!("producer failed");
+        });
+        assert!(handle.join().is_err());
+        assert_eq!(Err(TransducingError::Channel(TrySendError::Full(()))), completion.wait());
+        assert_eq!(vec![vec![0, 1]], rx.iter().collect::<Vec<_>>());
+
+        let (tx, rx) = shared_transducing_channel(transducers::partition_all(2));
+        let completion = tx.completion();
+        let other = tx.clone();
+        tx.send(1).unwrap();
+        other.send(2).unwrap();
+        other.send(3).unwrap();
+        tx.close().unwrap();
+        assert!(!completion.is_complete());
+        drop(other);
+        assert_eq!(Ok(()), completion.wait());
+        assert_eq!(vec![vec![1, 2], vec![3]], rx.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_transducing_receiver() {
+        use std::sync::mpsc::{self, RecvError, RecvTimeoutError, TryRecvError};
+        use std::time::Duration;
+
+        let (tx, rx) = mpsc::channel();
+        let mut rx: TransducingReceiver<_, _, (), _> =
+            transducing_receiver(rx, transducers::filter(|x: &usize| x & 1 == 0).partition_all(2));
+        assert_eq!(Err(TransducingError::Channel(TryRecvError::Empty)), rx.try_recv());
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        assert_eq!(Err(TransducingError::Channel(RecvTimeoutError::Timeout)), rx.recv_timeout(Duration::from_millis(10)));
+        tx.send(4).unwrap();
+        assert_eq!(Ok(vec![2, 4]), rx.recv_timeout(Duration::from_millis(10)));
+        thread::spawn(move|| {
+            for i in 5..11 {
+                tx.send(i).unwrap();
+            }
+        });
+        assert_eq!(vec![vec![6, 8], vec![10]], rx.iter().collect::<Vec<_>>());
+        assert!(rx.recv().is_err());
+        assert_eq!(Err(TransducingError::Channel(TryRecvError::Disconnected)), rx.try_recv());
+
+        let (tx, rx) = mpsc::channel();
+        let rx: TransducingReceiver<_, _, (), _> =
+            transducing_receiver(rx, transducers::mapcat(|x: usize| vec![x; x]).take(4));
+        for i in 1..4 {
+            tx.send(i).unwrap();
+        }
+        assert_eq!(vec![1, 2, 2, 3], rx.into_iter().collect::<Vec<_>>());
+
+        let (tx, rx) = mpsc::channel();
+        let mut rx: TransducingReceiver<_, _, ParseIntError, _> =
+            transducing_receiver(rx, transducers::try_map(|x: &str| x.parse::<usize>()));
+        tx.send("1").unwrap();
+        tx.send("x").unwrap();
+        tx.send("3").unwrap();
+        assert_eq!(Ok(1), rx.recv());
+        match rx.recv() {
+            Err(TransducingError::Reducing(e)) => assert_eq!("x".parse::<usize>().unwrap_err(), e),
+            other => panic!("expected a reducing error, got {:?}", other)
+        }
+        assert_eq!(Err(TransducingError::Channel(RecvError)), rx.recv());
+    }
 }
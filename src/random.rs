@@ -0,0 +1,90 @@
+/*
+ * Copyright 2016 rs-transducers developers
+ *
+ * Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+ * http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+ * <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+ * option. This file may not be copied, modified, or distributed
+ * except according to those terms.
+ */
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// A source of random numbers, as used by `random_sample`.  Any random number
+/// generator can be used by implementing `next_u64`.
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+
+    /// A number uniformly distributed in the range `[0, 1)`
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl<R> Rng for &mut R
+    where R: Rng {
+
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+}
+
+/// A small, fast, deterministic random number generator (SplitMix64).  Not
+/// suitable for cryptographic purposes.
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64
+}
+
+impl SplitMix64 {
+    /// A generator that always produces the same sequence for the same seed
+    pub fn seed(seed: u64) -> Self {
+        SplitMix64 {
+            state: seed
+        }
+    }
+
+    /// A generator seeded differently each time
+    pub fn new() -> Self {
+        SplitMix64::seed(RandomState::new().build_hasher().finish())
+    }
+}
+
+impl Default for SplitMix64 {
+    fn default() -> Self {
+        SplitMix64::new()
+    }
+}
+
+impl Rng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// A `SplitMix64` generator seeded differently each time it is created or
+/// cloned, so copies of a transducer using it sample independently.
+#[derive(Debug, Default)]
+pub struct Reseeding(SplitMix64);
+
+impl Reseeding {
+    pub fn new() -> Self {
+        Reseeding(SplitMix64::new())
+    }
+}
+
+impl Clone for Reseeding {
+    fn clone(&self) -> Self {
+        Reseeding::new()
+    }
+}
+
+impl Rng for Reseeding {
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+}
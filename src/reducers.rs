@@ -0,0 +1,778 @@
+/*
+ * Copyright 2016 rs-transducers developers
+ *
+ * Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+ * http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+ * <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+ * option. This file may not be copied, modified, or distributed
+ * except according to those terms.
+ */
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+use std::iter::{self, Product, Sum};
+use std::mem;
+use std::ops::{Add, Mul};
+
+use super::{Reducing, StepResult};
+
+#[derive(Clone)]
+pub struct FoldReducer<A, F> {
+    acc: Option<A>,
+    f: F
+}
+
+impl<A, F, I, E> Reducing<I, A, E> for FoldReducer<A, F>
+    where F: Fn(A, I) -> A {
+
+    type Item = I;
+
+    #[inline]
+    fn step(&mut self, value: I) -> Result<StepResult, E> {
+        let acc = self.acc.take().expect("Fold already completed");
+        self.acc = Some((self.f)(acc, value));
+        Ok(StepResult::Continue)
+    }
+
+    fn complete(&mut self) -> Result<A, E> {
+        Ok(self.acc.take().expect("Fold already completed"))
+    }
+}
+
+/// A reducing function that starts with `init` and combines it with each
+/// value using `f`, the final accumulated value is returned on completion.
+pub fn fold<A, F, I>(init: A, f: F) -> FoldReducer<A, F>
+    where F: Fn(A, I) -> A {
+
+    FoldReducer {
+        acc: Some(init),
+        f
+    }
+}
+
+fn increment<I>(n: usize, _: I) -> usize {
+    n + 1
+}
+
+/// A reducing function that counts the values.
+pub fn count<I>() -> FoldReducer<usize, fn(usize, I) -> usize> {
+    fold(0, increment)
+}
+
+/// A reducing function that adds the values together, returns zero if there
+/// are none.
+pub fn sum<T>() -> FoldReducer<T, fn(T, T) -> T>
+    where T: Sum + Add<Output=T> {
+
+    fold(iter::empty().sum(), Add::add)
+}
+
+/// A reducing function that multiplies the values together, returns one if
+/// there are none.
+pub fn product<T>() -> FoldReducer<T, fn(T, T) -> T>
+    where T: Product + Mul<Output=T> {
+
+    fold(iter::empty().product(), Mul::mul)
+}
+
+#[derive(Clone)]
+pub struct ExtremeReducer<T, F> {
+    acc: Option<T>,
+    compare: F,
+    max: bool
+}
+
+impl<T, F, E> Reducing<T, Option<T>, E> for ExtremeReducer<T, F>
+    where F: Fn(&T, &T) -> Ordering {
+
+    type Item = T;
+
+    #[inline]
+    fn step(&mut self, value: T) -> Result<StepResult, E> {
+        let replace = match self.acc {
+            None => true,
+            Some(ref acc) => {
+                let ordering = (self.compare)(&value, acc);
+                if self.max {
+                    ordering != Ordering::Less
+                } else {
+                    ordering == Ordering::Less
+                }
+            }
+        };
+        if replace {
+            self.acc = Some(value);
+        }
+        Ok(StepResult::Continue)
+    }
+
+    fn complete(&mut self) -> Result<Option<T>, E> {
+        Ok(self.acc.take())
+    }
+}
+
+/// A reducing function that returns the smallest value, or `None` if there
+/// are none.  As with `Iterator::min` the first is returned if several are
+/// equally small.
+pub fn min<T>() -> ExtremeReducer<T, fn(&T, &T) -> Ordering>
+    where T: Ord {
+
+    min_by(Ord::cmp)
+}
+
+/// A reducing function that returns the largest value, or `None` if there are
+/// none.  As with `Iterator::max` the last is returned if several are equally
+/// large.
+pub fn max<T>() -> ExtremeReducer<T, fn(&T, &T) -> Ordering>
+    where T: Ord {
+
+    max_by(Ord::cmp)
+}
+
+/// As `min`, but compares values with `compare`.
+pub fn min_by<T, F>(compare: F) -> ExtremeReducer<T, F>
+    where F: Fn(&T, &T) -> Ordering {
+
+    ExtremeReducer {
+        acc: None,
+        compare,
+        max: false
+    }
+}
+
+/// As `max`, but compares values with `compare`.
+pub fn max_by<T, F>(compare: F) -> ExtremeReducer<T, F>
+    where F: Fn(&T, &T) -> Ordering {
+
+    ExtremeReducer {
+        acc: None,
+        compare,
+        max: true
+    }
+}
+
+/// Values that `mean`, `moments`, `quantile` and `quantiles` can reduce.
+/// Unlike `Into<f64>` this covers every primitive number, including `u64`,
+/// `i64`, `usize` and `isize`, which are rounded to the nearest `f64` above
+/// 2^53.
+pub trait ToF64 {
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! to_f64 {
+    ($($t:ty),*) => {
+        $(
+            impl ToF64 for $t {
+                #[inline]
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    }
+}
+
+to_f64!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+#[derive(Clone, Default)]
+pub struct MeanReducer {
+    count: usize,
+    mean: f64
+}
+
+impl<T, E> Reducing<T, Option<f64>, E> for MeanReducer
+    where T: ToF64 {
+
+    type Item = T;
+
+    #[inline]
+    fn step(&mut self, value: T) -> Result<StepResult, E> {
+        self.count += 1;
+        self.mean += (value.to_f64() - self.mean) / self.count as f64;
+        Ok(StepResult::Continue)
+    }
+
+    fn complete(&mut self) -> Result<Option<f64>, E> {
+        if self.count == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(self.mean))
+        }
+    }
+}
+
+/// A reducing function that returns the arithmetic mean of the values, or
+/// `None` if there are none.  The mean is updated with each value rather than
+/// dividing a total, so large totals do not lose precision.
+pub fn mean() -> MeanReducer {
+    MeanReducer::default()
+}
+
+#[derive(Clone)]
+pub struct FirstReducer<T>(Option<T>);
+
+impl<T, E> Reducing<T, Option<T>, E> for FirstReducer<T> {
+    type Item = T;
+
+    #[inline]
+    fn step(&mut self, value: T) -> Result<StepResult, E> {
+        self.0 = Some(value);
+        Ok(StepResult::Stop)
+    }
+
+    fn complete(&mut self) -> Result<Option<T>, E> {
+        Ok(self.0.take())
+    }
+}
+
+/// A reducing function that returns the first value, or `None` if there are
+/// none.  Stops the reduction as soon as a value arrives.
+pub fn first<T>() -> FirstReducer<T> {
+    FirstReducer(None)
+}
+
+#[derive(Clone)]
+pub struct LastReducer<T>(Option<T>);
+
+impl<T, E> Reducing<T, Option<T>, E> for LastReducer<T> {
+    type Item = T;
+
+    #[inline]
+    fn step(&mut self, value: T) -> Result<StepResult, E> {
+        self.0 = Some(value);
+        Ok(StepResult::Continue)
+    }
+
+    fn complete(&mut self) -> Result<Option<T>, E> {
+        Ok(self.0.take())
+    }
+}
+
+/// A reducing function that returns the last value, or `None` if there are
+/// none.
+pub fn last<T>() -> LastReducer<T> {
+    LastReducer(None)
+}
+
+#[derive(Clone)]
+pub struct AnyReducer<F> {
+    f: F,
+    expect: bool,
+    found: bool
+}
+
+impl<F, T, E> Reducing<T, bool, E> for AnyReducer<F>
+    where F: Fn(&T) -> bool {
+
+    type Item = T;
+
+    #[inline]
+    fn step(&mut self, value: T) -> Result<StepResult, E> {
+        if (self.f)(&value) == self.expect {
+            self.found = true;
+            Ok(StepResult::Stop)
+        } else {
+            Ok(StepResult::Continue)
+        }
+    }
+
+    fn complete(&mut self) -> Result<bool, E> {
+        Ok(self.found == self.expect)
+    }
+}
+
+/// A reducing function that returns whether `pred` is true for any value.
+/// Stops the reduction at the first value for which it is.
+pub fn any<F, T>(pred: F) -> AnyReducer<F>
+    where F: Fn(&T) -> bool {
+
+    AnyReducer {
+        f: pred,
+        expect: true,
+        found: false
+    }
+}
+
+/// A reducing function that returns whether `pred` is true for every value,
+/// including when there are none.  Stops the reduction at the first value for
+/// which it is false.
+pub fn all<F, T>(pred: F) -> AnyReducer<F>
+    where F: Fn(&T) -> bool {
+
+    AnyReducer {
+        f: pred,
+        expect: false,
+        found: false
+    }
+}
+
+/// The count, mean and central moments of a stream of values, updated with
+/// each value using Welford's algorithm, which remains accurate when the
+/// values are large compared to their variance.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Moments {
+    count: usize,
+    mean: f64,
+    m2: f64,
+    m3: f64
+}
+
+impl Moments {
+    fn push(&mut self, value: f64) {
+        let n = self.count as f64 + 1.0;
+        let delta = value - self.mean;
+        let delta_n = delta / n;
+        let term = delta * delta_n * (n - 1.0);
+        self.count += 1;
+        self.mean += delta_n;
+        self.m3 += term * delta_n * (n - 2.0) - 3.0 * delta_n * self.m2;
+        self.m2 += term;
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// `None` if there are no values.
+    pub fn mean(&self) -> Option<f64> {
+        if self.count > 0 {
+            Some(self.mean)
+        } else {
+            None
+        }
+    }
+
+    /// The sample variance, `None` if there are fewer than two values.
+    pub fn variance(&self) -> Option<f64> {
+        if self.count > 1 {
+            Some(self.m2 / (self.count - 1) as f64)
+        } else {
+            None
+        }
+    }
+
+    /// The population variance, `None` if there are no values.
+    pub fn population_variance(&self) -> Option<f64> {
+        if self.count > 0 {
+            Some(self.m2 / self.count as f64)
+        } else {
+            None
+        }
+    }
+
+    /// The sample standard deviation, `None` if there are fewer than two
+    /// values.
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+
+    /// The population skewness, `None` if there are fewer than two values or
+    /// they are all equal.
+    pub fn skewness(&self) -> Option<f64> {
+        if self.count > 1 && self.m2 > 0.0 {
+            Some((self.count as f64).sqrt() * self.m3 / self.m2.powf(1.5))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MomentsReducer<F> {
+    moments: Moments,
+    f: F
+}
+
+impl<T, F, O, E> Reducing<T, O, E> for MomentsReducer<F>
+    where T: ToF64,
+          F: Fn(&Moments) -> O {
+
+    type Item = T;
+
+    #[inline]
+    fn step(&mut self, value: T) -> Result<StepResult, E> {
+        self.moments.push(value.to_f64());
+        Ok(StepResult::Continue)
+    }
+
+    fn complete(&mut self) -> Result<O, E> {
+        Ok((self.f)(&self.moments))
+    }
+}
+
+fn moments_reducer<O>(f: fn(&Moments) -> O) -> MomentsReducer<fn(&Moments) -> O> {
+    MomentsReducer {
+        moments: Moments::default(),
+        f
+    }
+}
+
+/// A reducing function that returns the `Moments` of the values, from which
+/// several statistics can be taken at once.
+pub fn moments() -> MomentsReducer<fn(&Moments) -> Moments> {
+    moments_reducer(|moments| *moments)
+}
+
+/// A reducing function that returns the sample variance of the values.
+pub fn variance() -> MomentsReducer<fn(&Moments) -> Option<f64>> {
+    moments_reducer(Moments::variance)
+}
+
+/// A reducing function that returns the sample standard deviation of the
+/// values.
+pub fn std_dev() -> MomentsReducer<fn(&Moments) -> Option<f64>> {
+    moments_reducer(Moments::std_dev)
+}
+
+/// A reducing function that returns the population skewness of the values.
+pub fn skewness() -> MomentsReducer<fn(&Moments) -> Option<f64>> {
+    moments_reducer(Moments::skewness)
+}
+
+/// An estimate of a single quantile using the P² algorithm of Jain and
+/// Chlamtac, which keeps five markers rather than the values themselves.
+#[derive(Clone)]
+struct QuantileSketch {
+    p: f64,
+    heights: Vec<f64>,
+    positions: [f64; 5],
+    desired: [f64; 5],
+    increments: [f64; 5]
+}
+
+impl QuantileSketch {
+    fn new(p: f64) -> Self {
+        assert!((0.0..=1.0).contains(&p), "quantile must be between zero and one");
+        QuantileSketch {
+            p,
+            heights: Vec::with_capacity(5),
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0]
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        if value.is_nan() {
+            return
+        }
+        if self.heights.len() < 5 {
+            self.heights.push(value);
+            self.heights.sort_by(f64::total_cmp);
+            return
+        }
+
+        let k = if value < self.heights[0] {
+            self.heights[0] = value;
+            0
+        } else if value >= self.heights[4] {
+            self.heights[4] = value;
+            3
+        } else {
+            (1..4).find(|&i| value < self.heights[i]).unwrap_or(4) - 1
+        };
+        for position in &mut self.positions[k + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(&self.increments) {
+            *desired += increment;
+        }
+
+        for i in 1..4 {
+            let d = self.desired[i] - self.positions[i];
+            if (d >= 1.0 && self.positions[i + 1] - self.positions[i] > 1.0) ||
+                (d <= -1.0 && self.positions[i - 1] - self.positions[i] < -1.0) {
+                let d = d.signum();
+                let height = self.parabolic(i, d);
+                self.heights[i] = if self.heights[i - 1] < height && height < self.heights[i + 1] {
+                    height
+                } else {
+                    self.linear(i, d)
+                };
+                self.positions[i] += d;
+            }
+        }
+    }
+
+    fn parabolic(&self, i: usize, d: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + d / (n[i + 1] - n[i - 1]) *
+            ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i]) +
+             (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    fn linear(&self, i: usize, d: f64) -> f64 {
+        let j = if d > 0.0 { i + 1 } else { i - 1 };
+        self.heights[i] + d * (self.heights[j] - self.heights[i]) / (self.positions[j] - self.positions[i])
+    }
+
+    /// Interpolates between the markers at the rank of the quantile, which
+    /// is also where the middle marker is moved to.  Each marker holds a value
+    /// at a known rank until it is adjusted, so this is exact up to five
+    /// values, and the minimum and maximum are always exact.
+    fn estimate(&self) -> Option<f64> {
+        let len = self.heights.len();
+        if len == 0 {
+            return None
+        }
+        let rank = 1.0 + self.p * (self.positions[len - 1] - 1.0);
+        if rank >= self.positions[len - 1] {
+            return Some(self.heights[len - 1])
+        }
+        let upper = (1..len).find(|&i| rank < self.positions[i]).unwrap_or(len - 1);
+        let lower = upper - 1;
+        let fraction = (rank - self.positions[lower]) / (self.positions[upper] - self.positions[lower]);
+        Some(self.heights[lower] + (self.heights[upper] - self.heights[lower]) * fraction)
+    }
+}
+
+#[derive(Clone)]
+pub struct QuantileReducer(QuantileSketch);
+
+impl<T, E> Reducing<T, Option<f64>, E> for QuantileReducer
+    where T: ToF64 {
+
+    type Item = T;
+
+    #[inline]
+    fn step(&mut self, value: T) -> Result<StepResult, E> {
+        self.0.push(value.to_f64());
+        Ok(StepResult::Continue)
+    }
+
+    fn complete(&mut self) -> Result<Option<f64>, E> {
+        Ok(self.0.estimate())
+    }
+}
+
+/// A reducing function that estimates the `p` quantile of the values, for
+/// example `quantile(0.99)` for the 99th percentile, using a fixed amount of
+/// memory however many values there are.  The estimate is exact for up to
+/// five values, and for `p` of zero or one.  NaN values are ignored, returns
+/// `None` if there are no other values.
+///
+/// Panics if `p` is not between zero and one.
+pub fn quantile(p: f64) -> QuantileReducer {
+    QuantileReducer(QuantileSketch::new(p))
+}
+
+#[derive(Clone)]
+pub struct QuantilesReducer(Vec<QuantileSketch>);
+
+impl<T, E> Reducing<T, Option<Vec<f64>>, E> for QuantilesReducer
+    where T: ToF64 {
+
+    type Item = T;
+
+    #[inline]
+    fn step(&mut self, value: T) -> Result<StepResult, E> {
+        let value = value.to_f64();
+        for sketch in &mut self.0 {
+            sketch.push(value);
+        }
+        Ok(StepResult::Continue)
+    }
+
+    fn complete(&mut self) -> Result<Option<Vec<f64>>, E> {
+        Ok(self.0.iter().map(QuantileSketch::estimate).collect())
+    }
+}
+
+/// As `quantile`, but estimates several quantiles in one pass, returning them
+/// in the same order.
+pub fn quantiles(ps: &[f64]) -> QuantilesReducer {
+    QuantilesReducer(ps.iter().map(|&p| QuantileSketch::new(p)).collect())
+}
+
+#[derive(Clone)]
+pub struct GroupByReducer<K, I, F> {
+    groups: HashMap<K, Vec<I>>,
+    f: F
+}
+
+impl<K, I, F, E> Reducing<I, HashMap<K, Vec<I>>, E> for GroupByReducer<K, I, F>
+    where F: Fn(&I) -> K,
+          K: Eq + Hash {
+
+    type Item = I;
+
+    #[inline]
+    fn step(&mut self, value: I) -> Result<StepResult, E> {
+        self.groups.entry((self.f)(&value)).or_default().push(value);
+        Ok(StepResult::Continue)
+    }
+
+    fn complete(&mut self) -> Result<HashMap<K, Vec<I>>, E> {
+        Ok(mem::take(&mut self.groups))
+    }
+}
+
+/// A reducing function that groups values by the result of `key_fn`, unlike
+/// `partition_by` values need not be consecutive to be grouped together.
+pub fn group_by<K, I, F>(key_fn: F) -> GroupByReducer<K, I, F>
+    where F: Fn(&I) -> K {
+
+    GroupByReducer {
+        groups: HashMap::new(),
+        f: key_fn
+    }
+}
+
+#[derive(Clone)]
+pub struct GroupByIntoReducer<K, R, F> {
+    groups: HashMap<K, (R, bool)>,
+    inner: R,
+    f: F
+}
+
+impl<K, R, F, I, O, E> Reducing<I, HashMap<K, O>, E> for GroupByIntoReducer<K, R, F>
+    where F: Fn(&I) -> K,
+          K: Eq + Hash,
+          R: Reducing<I, O, E> + Clone {
+
+    type Item = I;
+
+    #[inline]
+    fn step(&mut self, value: I) -> Result<StepResult, E> {
+        let inner = &self.inner;
+        let &mut (ref mut rf, ref mut stopped) = self.groups.entry((self.f)(&value)).or_insert_with(|| {
+            let mut rf = inner.clone();
+            rf.init();
+            (rf, false)
+        });
+        if !*stopped {
+            *stopped = rf.step(value)?.is_stop();
+        }
+        Ok(StepResult::Continue)
+    }
+
+    fn complete(&mut self) -> Result<HashMap<K, O>, E> {
+        let mut result = HashMap::with_capacity(self.groups.len());
+        for (key, (mut rf, _)) in self.groups.drain() {
+            result.insert(key, rf.complete()?);
+        }
+        Ok(result)
+    }
+}
+
+/// A reducing function that groups values by the result of `key_fn`, and
+/// reduces each group with its own clone of `inner`.  Once the reduction of a
+/// group stops any further values for that key are ignored, other groups
+/// continue.
+pub fn group_by_into<K, R, F, I>(key_fn: F, inner: R) -> GroupByIntoReducer<K, R, F>
+    where F: Fn(&I) -> K {
+
+    GroupByIntoReducer {
+        groups: HashMap::new(),
+        inner,
+        f: key_fn
+    }
+}
+
+#[derive(Clone)]
+pub struct FrequenciesReducer<T>(HashMap<T, usize>);
+
+impl<T, E> Reducing<T, HashMap<T, usize>, E> for FrequenciesReducer<T>
+    where T: Eq + Hash {
+
+    type Item = T;
+
+    #[inline]
+    fn step(&mut self, value: T) -> Result<StepResult, E> {
+        *self.0.entry(value).or_default() += 1;
+        Ok(StepResult::Continue)
+    }
+
+    fn complete(&mut self) -> Result<HashMap<T, usize>, E> {
+        Ok(mem::take(&mut self.0))
+    }
+}
+
+/// A reducing function that counts how many times each distinct value
+/// occurs.
+pub fn frequencies<T>() -> FrequenciesReducer<T> {
+    FrequenciesReducer(HashMap::new())
+}
+
+/// A value in the heap of a `TopKReducer`, ordered so that the greatest is
+/// the one to be evicted first: the smallest key, or the latest of equal
+/// keys.
+#[derive(Clone)]
+struct Ranked<K, T> {
+    key: K,
+    seq: usize,
+    value: T
+}
+
+impl<K: Ord, T> Ord for Ranked<K, T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key.cmp(&self.key).then(self.seq.cmp(&other.seq))
+    }
+}
+
+impl<K: Ord, T> PartialOrd for Ranked<K, T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, T> PartialEq for Ranked<K, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<K: Ord, T> Eq for Ranked<K, T> {}
+
+#[derive(Clone)]
+pub struct TopKReducer<K, T, F> {
+    k: usize,
+    seq: usize,
+    heap: BinaryHeap<Ranked<K, T>>,
+    f: F
+}
+
+impl<K, T, F, E> Reducing<T, Vec<T>, E> for TopKReducer<K, T, F>
+    where F: Fn(&T) -> K,
+          K: Ord {
+
+    type Item = T;
+
+    #[inline]
+    fn step(&mut self, value: T) -> Result<StepResult, E> {
+        let ranked = Ranked {
+            key: (self.f)(&value),
+            seq: self.seq,
+            value
+        };
+        self.seq += 1;
+        if self.heap.len() < self.k {
+            self.heap.push(ranked);
+        } else if let Some(mut worst) = self.heap.peek_mut() {
+            if ranked < *worst {
+                *worst = ranked;
+            }
+        }
+        Ok(StepResult::Continue)
+    }
+
+    fn complete(&mut self) -> Result<Vec<T>, E> {
+        let heap = mem::take(&mut self.heap);
+        Ok(heap.into_sorted_vec().into_iter().map(|ranked| ranked.value).collect())
+    }
+}
+
+/// A reducing function that returns the `k` values with the largest result of
+/// `by`, largest first, keeping no more than `k` values at any time.  Of
+/// values with equal results, the earliest are kept.
+pub fn top_k<K, T, F>(k: usize, by: F) -> TopKReducer<K, T, F>
+    where F: Fn(&T) -> K {
+
+    TopKReducer {
+        k,
+        seq: 0,
+        heap: BinaryHeap::with_capacity(k),
+        f: by
+    }
+}
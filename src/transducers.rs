@@ -7,50 +7,28 @@
  * option. This file may not be copied, modified, or distributed
  * except according to those terms.
  */
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::hash::Hash;
 use std::marker::PhantomData;
 use std::mem;
 
-use super::{Transducer, Reducing, StepResult};
+use super::{Transducer, TransducerExt, Reducing, StepResult};
+use super::random::{Rng, Reseeding};
 
-pub struct MapTransducer<F> {
-    f: F
-}
-
-pub struct MapReducer<R, F> {
-    rf: R,
-    t: MapTransducer<F>
-}
+transducer! {
+    #[derive(Clone)]
+    pub struct MapTransducer<F> { f: F }
 
-impl<F, RI> Transducer<RI> for MapTransducer<F> {
-    type RO = MapReducer<RI, F>;
+    pub struct MapReducer {}
 
-    fn new(self, reducing_fn: RI) -> Self::RO {
-        MapReducer {
-            rf: reducing_fn,
-            t: self
-        }
-    }
-}
-
-impl<R, F, I, O, OF, E> Reducing<I, OF, E> for MapReducer<R, F>
-    where F: Fn(I) -> O,
-          R: Reducing<O, OF, E> {
-
-    type Item = O;
-
-    fn init(&mut self) {
-        self.rf.init();
-    }
+    impl<I, O> Reducing for MapReducer
+        where F: Fn(I) -> O {
 
-    #[inline]
-    fn step(&mut self, value: I) -> Result<StepResult, E> {
-        self.rf.step((self.t.f)(value))
-    }
+        type Item = O;
 
-    fn complete(&mut self) -> Result<(), E> {
-        self.rf.complete()
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            self.rf.step((self.t.f)(value))
+        }
     }
 }
 
@@ -58,51 +36,54 @@ pub fn map<F, I, O>(f: F) -> MapTransducer<F>
     where F: Fn(I) -> O {
 
     MapTransducer {
-        f: f
+        f
     }
 }
 
-pub struct MapIndexedTransducer<F> {
-    f: F
-}
+transducer! {
+    #[derive(Clone)]
+    pub struct TryMapTransducer<F> { f: F }
 
-pub struct MapIndexedReducer<R, F> {
-    rf: R,
-    t: MapIndexedTransducer<F>,
-    count: usize
-}
+    pub struct TryMapReducer {}
 
-impl<F, RI> Transducer<RI> for MapIndexedTransducer<F> {
-    type RO = MapIndexedReducer<RI, F>;
+    impl<I, O, FE> Reducing for TryMapReducer
+        where F: Fn(I) -> Result<O, FE>,
+              E: From<FE> {
 
-    fn new(self, reducing_fn: RI) -> Self::RO {
-        MapIndexedReducer {
-            rf: reducing_fn,
-            t: self,
-            count: 0
+        type Item = O;
+
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            self.rf.step((self.t.f)(value)?)
         }
     }
 }
 
-impl<R, F, I, O, OF, E> Reducing<I, OF, E> for MapIndexedReducer<R, F>
-    where F: Fn(usize, I) -> O,
-          R: Reducing<O, OF, E> {
+/// As `map`, but the function can fail.  An error is converted into the
+/// error type of the reducing function, ending the reduction.
+pub fn try_map<F, I, O, FE>(f: F) -> TryMapTransducer<F>
+    where F: Fn(I) -> Result<O, FE> {
 
-    type Item = O;
-
-    fn init(&mut self) {
-        self.rf.init();
+    TryMapTransducer {
+        f
     }
+}
 
-    #[inline]
-    fn step(&mut self, value: I) -> Result<StepResult, E> {
-        let idx = self.count;
-        self.count += 1;
-        self.rf.step((self.t.f)(idx, value))
-    }
+transducer! {
+    #[derive(Clone)]
+    pub struct MapIndexedTransducer<F> { f: F }
 
-    fn complete(&mut self) -> Result<(), E> {
-        self.rf.complete()
+    pub struct MapIndexedReducer { count: usize = 0 }
+
+    impl<I, O> Reducing for MapIndexedReducer
+        where F: Fn(usize, I) -> O {
+
+        type Item = O;
+
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            let idx = self.count;
+            self.count += 1;
+            self.rf.step((self.t.f)(idx, value))
+        }
     }
 }
 
@@ -110,55 +91,63 @@ pub fn map_indexed<F, I, O>(f: F) -> MapIndexedTransducer<F>
     where F: Fn(usize, I) -> O {
 
     MapIndexedTransducer {
-        f: f
+        f
     }
 }
 
-pub struct MapcatTransducer<F> {
-    f: F
-}
+transducer! {
+    #[derive(Clone)]
+    pub struct ScanTransducer<S, F> { state: S, f: F }
 
-pub struct MapcatReducer<R, F> {
-    rf: R,
-    t: MapcatTransducer<F>
-}
+    pub struct ScanReducer {}
 
-impl<F, RI> Transducer<RI> for MapcatTransducer<F> {
-    type RO = MapcatReducer<RI, F>;
+    impl<I> Reducing for ScanReducer
+        where S: Clone,
+              F: Fn(&S, I) -> S {
 
-    fn new(self, reducing_fn: RI) -> Self::RO {
-        MapcatReducer {
-            rf: reducing_fn,
-            t: self
+        type Item = S;
+
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            self.t.state = (self.t.f)(&self.t.state, value);
+            self.rf.step(self.t.state.clone())
         }
     }
 }
 
-impl<R, F, I, O, IO, OF, E> Reducing<I, OF, E> for MapcatReducer<R, F>
-    where IO: IntoIterator<Item=O>,
-          F: Fn(I) -> IO,
-          R: Reducing<O, OF, E> {
-
-    type Item = O;
+/// Threads a state through each value, starting with `init`, and produces
+/// each new state.  The equivalent of Clojure's `reductions`, except that
+/// `init` itself is not produced.
+pub fn scan<S, F, I>(init: S, f: F) -> ScanTransducer<S, F>
+    where F: Fn(&S, I) -> S {
 
-    fn init(&mut self) {
-        self.rf.init();
+    ScanTransducer {
+        state: init,
+        f
     }
+}
 
-    #[inline]
-    fn step(&mut self, value: I) -> Result<StepResult, E> {
-        for o in (self.t.f)(value) {
-            match self.rf.step(o) {
-                Ok(StepResult::Continue) => (),
-                Ok(StepResult::Stop) => return Ok(StepResult::Stop),
-                Err(e) => return Err(e)
+transducer! {
+    #[derive(Clone)]
+    pub struct MapcatTransducer<F> { f: F }
+
+    pub struct MapcatReducer {}
+
+    impl<I, O, IO> Reducing for MapcatReducer
+        where IO: IntoIterator<Item=O>,
+              F: Fn(I) -> IO {
+
+        type Item = O;
+
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            for o in (self.t.f)(value) {
+                match self.rf.step(o) {
+                    Ok(StepResult::Continue) => (),
+                    Ok(StepResult::Stop) => return Ok(StepResult::Stop),
+                    Err(e) => return Err(e)
+                }
             }
+            Ok(StepResult::Continue)
         }
-        Ok(StepResult::Continue)
-    }
-
-    fn complete(&mut self) -> Result<(), E> {
-        self.rf.complete()
     }
 }
 
@@ -167,63 +156,40 @@ pub fn mapcat<F, I, O, IO>(f: F) -> MapcatTransducer<F>
           F: Fn(I) -> IO {
 
     MapcatTransducer {
-        f: f
+        f
     }
 }
 
-pub struct FilterTransducer<F> {
-    f: F,
-    inclusive: bool
-}
+transducer! {
+    #[derive(Clone)]
+    pub struct FilterTransducer<F> { f: F, inclusive: bool }
 
-pub struct FilterReducer<R, F> {
-    rf: R,
-    t: FilterTransducer<F>
-}
-
-impl<F, RI> Transducer<RI> for FilterTransducer<F> {
-    type RO = FilterReducer<RI, F>;
-
-    fn new(self, reducing_fn: RI) -> Self::RO {
-        FilterReducer {
-            rf: reducing_fn,
-            t: self
-        }
-    }
-}
+    pub struct FilterReducer {}
 
-impl<R, F, I, OF, E> Reducing<I, OF, E> for FilterReducer<R, F>
-    where F: Fn(&I) -> bool,
-          R: Reducing<I, OF, E> {
-    type Item = I;
+    impl<I> Reducing for FilterReducer
+        where F: Fn(&I) -> bool {
 
-    fn init(&mut self) {
-        self.rf.init();
-    }
+        type Item = I;
 
-    #[inline]
-    fn step(&mut self, value: I) -> Result<StepResult, E> {
-        let mut include = (self.t.f)(&value);
-        if !self.t.inclusive {
-            include = !include;
-        }
-        if include {
-            self.rf.step(value)
-        } else {
-            Ok(StepResult::Continue)
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            let mut include = (self.t.f)(&value);
+            if !self.t.inclusive {
+                include = !include;
+            }
+            if include {
+                self.rf.step(value)
+            } else {
+                Ok(StepResult::Continue)
+            }
         }
     }
-
-    fn complete(&mut self) -> Result<(), E> {
-        self.rf.complete()
-    }
 }
 
 pub fn filter<F, T>(f: F) -> FilterTransducer<F>
     where F: Fn(&T) -> bool {
 
     FilterTransducer {
-        f: f,
+        f,
         inclusive: true
     }
 }
@@ -232,108 +198,131 @@ pub fn remove<F, T>(f: F) -> FilterTransducer<F>
     where F: Fn(&T) -> bool {
 
     FilterTransducer {
-        f: f,
+        f,
         inclusive: false
     }
 }
 
-pub struct KeepTransducer<F>(F);
+transducer! {
+    #[derive(Clone)]
+    pub struct TryFilterTransducer<F> { f: F }
 
-pub struct KeepReducer<R, F> {
-    rf: R,
-    t: KeepTransducer<F>
-}
+    pub struct TryFilterReducer {}
 
-impl<F, RI> Transducer<RI> for KeepTransducer<F> {
-    type RO = KeepReducer<RI, F>;
+    impl<I, FE> Reducing for TryFilterReducer
+        where F: Fn(&I) -> Result<bool, FE>,
+              E: From<FE> {
 
-    fn new(self, reducing_fn: RI) -> Self::RO {
-        KeepReducer {
-            rf: reducing_fn,
-            t: self
+        type Item = I;
+
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            if (self.t.f)(&value)? {
+                self.rf.step(value)
+            } else {
+                Ok(StepResult::Continue)
+            }
         }
     }
 }
 
-impl<R, F, I, O, OF, E> Reducing<I, OF, E> for KeepReducer<R, F>
-    where F: Fn(I) -> Option<O>,
-          R: Reducing<O, OF, E> {
-
-    type Item = O;
+/// As `filter`, but the predicate can fail.  An error is converted into the
+/// error type of the reducing function, ending the reduction.
+pub fn try_filter<F, T, FE>(f: F) -> TryFilterTransducer<F>
+    where F: Fn(&T) -> Result<bool, FE> {
 
-    fn init(&mut self) {
-        self.rf.init();
+    TryFilterTransducer {
+        f
     }
+}
 
-    #[inline]
-    fn step(&mut self, value: I) -> Result<StepResult, E> {
-        match (self.t.0)(value) {
-            Some(o) => self.rf.step(o),
-            None => Ok(StepResult::Continue)
-        }
-    }
+transducer! {
+    #[derive(Clone)]
+    pub struct KeepTransducer<F> { f: F }
 
-    fn complete(&mut self) -> Result<(), E> {
-        self.rf.complete()
+    pub struct KeepReducer {}
+
+    impl<I, O> Reducing for KeepReducer
+        where F: Fn(I) -> Option<O> {
+
+        type Item = O;
+
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            match (self.t.f)(value) {
+                Some(o) => self.rf.step(o),
+                None => Ok(StepResult::Continue)
+            }
+        }
     }
 }
 
 pub fn keep<F, I, O>(f: F) -> KeepTransducer<F>
     where F: Fn(I) -> Option<O> {
 
-    KeepTransducer(f)
+    KeepTransducer {
+        f
+    }
 }
 
-pub struct KeepIndexedTransducer<F>(F);
+transducer! {
+    #[derive(Clone)]
+    pub struct TryKeepTransducer<F> { f: F }
 
-pub struct KeepIndexedReducer<R, F> {
-    rf: R,
-    t: KeepIndexedTransducer<F>,
-    count: usize
-}
+    pub struct TryKeepReducer {}
 
-impl<F, RI> Transducer<RI> for KeepIndexedTransducer<F> {
-    type RO = KeepIndexedReducer<RI, F>;
+    impl<I, O, FE> Reducing for TryKeepReducer
+        where F: Fn(I) -> Result<Option<O>, FE>,
+              E: From<FE> {
 
-    fn new(self, reducing_fn: RI) -> Self::RO {
-        KeepIndexedReducer {
-            rf: reducing_fn,
-            t: self,
-            count: 0
+        type Item = O;
+
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            match (self.t.f)(value)? {
+                Some(o) => self.rf.step(o),
+                None => Ok(StepResult::Continue)
+            }
         }
     }
 }
 
-impl<R, F, I, O, OF, E> Reducing<I, OF, E> for KeepIndexedReducer<R, F>
-    where F: Fn(usize, I) -> Option<O>,
-          R: Reducing<O, OF, E> {
+/// As `keep`, but the function can fail.  An error is converted into the
+/// error type of the reducing function, ending the reduction.
+pub fn try_keep<F, I, O, FE>(f: F) -> TryKeepTransducer<F>
+    where F: Fn(I) -> Result<Option<O>, FE> {
 
-    type Item = O;
-
-    fn init(&mut self) {
-        self.rf.init();
+    TryKeepTransducer {
+        f
     }
+}
 
-    #[inline]
-    fn step(&mut self, value: I) -> Result<StepResult, E> {
-        let idx = self.count;
-        self.count += 1;
+transducer! {
+    #[derive(Clone)]
+    pub struct KeepIndexedTransducer<F> { f: F }
 
-        match (self.t.0)(idx, value) {
-            Some(o) => self.rf.step(o),
-            None => Ok(StepResult::Continue)
-        }
-    }
+    pub struct KeepIndexedReducer { count: usize = 0 }
 
-    fn complete(&mut self) -> Result<(), E> {
-        self.rf.complete()
+    impl<I, O> Reducing for KeepIndexedReducer
+        where F: Fn(usize, I) -> Option<O> {
+
+        type Item = O;
+
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            let idx = self.count;
+            self.count += 1;
+
+            match (self.t.f)(idx, value) {
+                Some(o) => self.rf.step(o),
+                None => Ok(StepResult::Continue)
+            }
+        }
     }
 }
 
 pub fn keep_indexed<F, I, O>(f: F) -> KeepIndexedTransducer<F>
     where F: Fn(usize, I) -> Option<O> {
 
-    KeepIndexedTransducer(f)
+    KeepIndexedTransducer {
+        f
+    }
 }
 
 pub struct PartitionTransducer<T> {
@@ -342,6 +331,16 @@ pub struct PartitionTransducer<T> {
     t: PhantomData<T>
 }
 
+impl<T> Clone for PartitionTransducer<T> {
+    fn clone(&self) -> Self {
+        PartitionTransducer {
+            size: self.size,
+            all: self.all,
+            t: PhantomData
+        }
+    }
+}
+
 pub struct PartitionReducer<RF, T> {
     t: PartitionTransducer<T>,
     rf: RF,
@@ -361,6 +360,8 @@ impl<RI, T> Transducer<RI> for PartitionTransducer<T> {
     }
 }
 
+impl<T> TransducerExt for PartitionTransducer<T> {}
+
 impl<R, I, OF, E> Reducing<I, OF, E> for PartitionReducer<R, I>
     where R: Reducing<Vec<I>, OF, E> {
 
@@ -382,11 +383,16 @@ impl<R, I, OF, E> Reducing<I, OF, E> for PartitionReducer<R, I>
         }
     }
 
-    fn complete(&mut self) -> Result<(), E> {
-        if self.t.all {
+    #[inline]
+    fn accumulated(&mut self) -> Option<&mut OF> {
+        self.rf.accumulated()
+    }
+
+    fn complete(&mut self) -> Result<OF, E> {
+        if self.t.all && !self.holder.is_empty() {
             let mut other_holder = Vec::new();
             mem::swap(&mut other_holder, &mut self.holder);
-            try!(self.rf.step(other_holder));
+            self.rf.step(other_holder)?;
         }
         self.rf.complete()
     }
@@ -408,257 +414,247 @@ pub fn partition_all<T>(num: usize) -> PartitionTransducer<T> {
     }
 }
 
-pub struct TakeTransducer(usize);
+transducer! {
+    pub struct WindowTransducer<T> { size: usize, step: usize, t: PhantomData<T> }
 
-pub struct TakeReducer<RF> {
-    rf: RF,
-    taken: usize,
-    t: TakeTransducer
-}
+    pub struct WindowReducer { skip: usize = 0, holder: VecDeque<T> = VecDeque::new() }
 
-impl<RI> Transducer<RI> for TakeTransducer {
-    type RO = TakeReducer<RI>;
+    impl Reducing for WindowReducer
+        where T: Clone {
 
-    fn new(self, reducing_fn: RI) -> Self::RO {
-        TakeReducer {
-            rf: reducing_fn,
-            taken: 0,
-            t: self
-        }
-    }
-}
+        type Item = Vec<T>;
 
-impl<R, I, OF, E> Reducing<I, OF, E> for TakeReducer<R>
-    where R: Reducing<I, OF, E> {
-
-    type Item = I;
-
-    fn init(&mut self) {
-        self.rf.init();
-    }
-
-    #[inline]
-    fn step(&mut self, value: I) -> Result<StepResult, E> {
-        if self.taken < self.t.0 {
-            self.taken += 1;
-            match self.rf.step(value) {
-                Ok(StepResult::Continue) => if self.taken < self.t.0 {
-                    Ok(StepResult::Continue)
+        fn step(&mut self, value: T) -> Result<StepResult, E> {
+            if self.skip > 0 {
+                self.skip -= 1;
+                return Ok(StepResult::Continue)
+            }
+            self.holder.push_back(value);
+            if self.holder.len() == self.t.size {
+                let window = self.holder.iter().cloned().collect();
+                if self.t.step < self.t.size {
+                    self.holder.drain(..self.t.step);
                 } else {
-                    Ok(StepResult::Stop)
-                },
-                Ok(StepResult::Stop) => Ok(StepResult::Stop),
-                Err(e) => Err(e)
+                    self.holder.clear();
+                    self.skip = self.t.step - self.t.size;
+                }
+                self.rf.step(window)
+            } else {
+                Ok(StepResult::Continue)
             }
-        } else {
-            Ok(StepResult::Stop)
         }
     }
+}
 
-    fn complete(&mut self) -> Result<(), E> {
-        self.rf.complete()
+impl<T> Clone for WindowTransducer<T> {
+    fn clone(&self) -> Self {
+        WindowTransducer {
+            size: self.size,
+            step: self.step,
+            t: PhantomData
+        }
     }
 }
 
-pub fn take(num: usize) -> TakeTransducer {
-    TakeTransducer(num)
+/// Windows of `size` values, starting every `step` values.  Where `step` is
+/// less than `size` the windows overlap and values are cloned into each,
+/// where it is greater values between windows are dropped.  Only full
+/// windows are produced, so `window(n, n)` is equivalent to `partition(n)`.
+///
+/// Panics if `size` or `step` is zero.
+pub fn window<T>(size: usize, step: usize) -> WindowTransducer<T> {
+    assert!(size > 0, "window size must be greater than zero");
+    assert!(step > 0, "window step must be greater than zero");
+    WindowTransducer {
+        size,
+        step,
+        t: PhantomData
+    }
 }
 
-pub struct TakeWhileTransducer<F>(F);
+transducer! {
+    pub struct PairwiseTransducer<T> { t: PhantomData<T> }
 
-pub struct TakeWhileReducer<RF, F> {
-    rf: RF,
-    t: TakeWhileTransducer<F>
-}
+    pub struct PairwiseReducer { prev: Option<T> = None }
 
-impl<RI, F> Transducer<RI> for TakeWhileTransducer<F> {
-    type RO = TakeWhileReducer<RI, F>;
+    impl Reducing for PairwiseReducer
+        where T: Clone {
 
-    fn new(self, reducing_fn: RI) -> Self::RO {
-        TakeWhileReducer {
-            rf: reducing_fn,
-            t: self
+        type Item = (T, T);
+
+        fn step(&mut self, value: T) -> Result<StepResult, E> {
+            match self.prev.replace(value.clone()) {
+                Some(prev) => self.rf.step((prev, value)),
+                None => Ok(StepResult::Continue)
+            }
         }
     }
 }
 
-impl<R, I, OF, E, F> Reducing<I, OF, E> for TakeWhileReducer<R, F>
-    where R: Reducing<I, OF, E>,
-          F: Fn(&I) -> bool {
-
-    type Item = I;
-
-    fn init(&mut self) {
-        self.rf.init();
-    }
-
-    #[inline]
-    fn step(&mut self, value: I) -> Result<StepResult, E> {
-        if (self.t.0)(&value) {
-            self.rf.step(value)
-        } else {
-            Ok(StepResult::Stop)
+impl<T> Clone for PairwiseTransducer<T> {
+    fn clone(&self) -> Self {
+        PairwiseTransducer {
+            t: PhantomData
         }
     }
+}
 
-    fn complete(&mut self) -> Result<(), E> {
-        self.rf.complete()
+/// Each value paired with the one before it, a tuple equivalent of
+/// `window(2, 1)`.
+pub fn pairwise<T>() -> PairwiseTransducer<T> {
+    PairwiseTransducer {
+        t: PhantomData
     }
 }
 
-pub fn take_while<F, T>(pred: F) -> TakeWhileTransducer<F>
-    where F: Fn(&T) -> bool {
+transducer! {
+    #[derive(Clone)]
+    pub struct TakeTransducer { num: usize }
 
-    TakeWhileTransducer(pred)
-}
+    pub struct TakeReducer { taken: usize = 0 }
 
-pub struct DropWhileTransducer<F>(F);
+    impl<I> Reducing for TakeReducer {
+        type Item = I;
 
-pub struct DropWhileReducer<RF, F> {
-    rf: RF,
-    t: DropWhileTransducer<F>,
-    done: bool
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            if self.taken < self.t.num {
+                self.taken += 1;
+                match self.rf.step(value) {
+                    Ok(StepResult::Continue) => if self.taken < self.t.num {
+                        Ok(StepResult::Continue)
+                    } else {
+                        Ok(StepResult::Stop)
+                    },
+                    Ok(StepResult::Stop) => Ok(StepResult::Stop),
+                    Err(e) => Err(e)
+                }
+            } else {
+                Ok(StepResult::Stop)
+            }
+        }
+    }
 }
 
-impl<RI, F> Transducer<RI> for DropWhileTransducer<F> {
-    type RO = DropWhileReducer<RI, F>;
-
-    fn new(self, reducing_fn: RI) -> Self::RO {
-        DropWhileReducer {
-            rf: reducing_fn,
-            t: self,
-            done: false
-        }
+pub fn take(num: usize) -> TakeTransducer {
+    TakeTransducer {
+        num
     }
 }
 
-impl<R, I, OF, E, F> Reducing<I, OF, E> for DropWhileReducer<R, F>
-    where R: Reducing<I, OF, E>,
-          F: Fn(&I) -> bool {
+transducer! {
+    #[derive(Clone)]
+    pub struct TakeWhileTransducer<F> { f: F }
 
-    type Item = I;
+    pub struct TakeWhileReducer {}
 
-    fn init(&mut self) {
-        self.rf.init();
-    }
+    impl<I> Reducing for TakeWhileReducer
+        where F: Fn(&I) -> bool {
 
-    #[inline]
-    fn step(&mut self, value: I) -> Result<StepResult, E> {
-        if self.done {
-            self.rf.step(value)
-        } else {
-            if !(self.t.0)(&value) {
-                self.done = true;
+        type Item = I;
+
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            if (self.t.f)(&value) {
                 self.rf.step(value)
             } else {
-                Ok(StepResult::Continue)
+                Ok(StepResult::Stop)
             }
         }
     }
-
-    fn complete(&mut self) -> Result<(), E> {
-        self.rf.complete()
-    }
 }
 
-pub fn drop_while<F, T>(pred: F) -> DropWhileTransducer<F>
+pub fn take_while<F, T>(pred: F) -> TakeWhileTransducer<F>
     where F: Fn(&T) -> bool {
 
-    DropWhileTransducer(pred)
+    TakeWhileTransducer {
+        f: pred
+    }
 }
 
-pub struct DropTransducer(usize);
+transducer! {
+    #[derive(Clone)]
+    pub struct DropWhileTransducer<F> { f: F }
 
-pub struct DropReducer<RF> {
-    rf: RF,
-    dropped: usize,
-    d: DropTransducer
-}
+    pub struct DropWhileReducer { done: bool = false }
 
-impl<RI> Transducer<RI> for DropTransducer {
-    type RO = DropReducer<RI>;
+    impl<I> Reducing for DropWhileReducer
+        where F: Fn(&I) -> bool {
 
-    fn new(self, reducing_fn: RI) -> Self::RO {
-        DropReducer {
-            rf: reducing_fn,
-            dropped: 0,
-            d: self
+        type Item = I;
+
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            if self.done {
+                self.rf.step(value)
+            } else {
+                if !(self.t.f)(&value) {
+                    self.done = true;
+                    self.rf.step(value)
+                } else {
+                    Ok(StepResult::Continue)
+                }
+            }
         }
     }
 }
 
-impl<R, I, OF, E> Reducing<I, OF, E> for DropReducer<R>
-    where R: Reducing<I, OF, E> {
-
-    type Item = I;
-
-    fn init(&mut self) {
-        self.rf.init();
-    }
-
-    #[inline]
-    fn step(&mut self, value: I) -> Result<StepResult, E> {
-        if self.dropped < self.d.0 {
-            self.dropped += 1;
-            Ok(StepResult::Continue)
-        } else {
-            self.rf.step(value)
-        }
-    }
+pub fn drop_while<F, T>(pred: F) -> DropWhileTransducer<F>
+    where F: Fn(&T) -> bool {
 
-    fn complete(&mut self) -> Result<(), E> {
-        self.rf.complete()
+    DropWhileTransducer {
+        f: pred
     }
 }
 
-pub fn drop(size: usize) -> DropTransducer {
-    DropTransducer(size)
-}
-
-pub struct ReplaceTransducer<T>(HashMap<T, T>);
+transducer! {
+    #[derive(Clone)]
+    pub struct DropTransducer { num: usize }
 
-pub struct ReplaceReducer<RF, T> {
-    rf: RF,
-    t: ReplaceTransducer<T>
-}
+    pub struct DropReducer { dropped: usize = 0 }
 
-impl <'a, RI, T> Transducer<RI> for ReplaceTransducer<T> {
-    type RO = ReplaceReducer<RI, T>;
+    impl<I> Reducing for DropReducer {
+        type Item = I;
 
-    fn new(self, reducing_fn: RI) -> Self::RO {
-        ReplaceReducer {
-            rf: reducing_fn,
-            t: self
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            if self.dropped < self.t.num {
+                self.dropped += 1;
+                Ok(StepResult::Continue)
+            } else {
+                self.rf.step(value)
+            }
         }
     }
 }
 
-impl<'a, R, I, OF, E> Reducing<I, OF, E> for ReplaceReducer<R, I>
-    where I: Eq + Hash + Clone,
-          R: Reducing<I, OF, E> {
+pub fn drop(size: usize) -> DropTransducer {
+    DropTransducer {
+        num: size
+    }
+}
 
-    type Item = I;
+transducer! {
+    #[derive(Clone)]
+    pub struct ReplaceTransducer<T> { replacements: HashMap<T, T> }
 
-    fn init(&mut self) {
-        self.rf.init();
-    }
+    pub struct ReplaceReducer {}
 
-    #[inline]
-    fn step(&mut self, value: I) -> Result<StepResult, E> {
-        let v:I = match self.t.0.get(&value) {
-            Some(val) => val.clone(),
-            None => value
-        };
-        self.rf.step(v)
-    }
+    impl Reducing for ReplaceReducer
+        where T: Eq + Hash + Clone {
 
-    fn complete(&mut self) -> Result<(), E> {
-        self.rf.complete()
+        type Item = T;
+
+        fn step(&mut self, value: T) -> Result<StepResult, E> {
+            let v:T = match self.t.replacements.get(&value) {
+                Some(val) => val.clone(),
+                None => value
+            };
+            self.rf.step(v)
+        }
     }
 }
 
 pub fn replace<T>(replacements: HashMap<T, T>) -> ReplaceTransducer<T> {
-    ReplaceTransducer(replacements)
+    ReplaceTransducer {
+        replacements
+    }
 }
 
 pub struct PartitionByTransducer<F, T, R>
@@ -668,6 +664,17 @@ pub struct PartitionByTransducer<F, T, R>
     t: PhantomData<T>
 }
 
+impl<F, T, R> Clone for PartitionByTransducer<F, T, R>
+    where F: Fn(&T) -> R + Clone {
+
+    fn clone(&self) -> Self {
+        PartitionByTransducer {
+            f: self.f.clone(),
+            t: PhantomData
+        }
+    }
+}
+
 pub struct PartitionByReducer<RF, F, T, R>
     where F: Fn(&T) -> R {
 
@@ -692,6 +699,9 @@ impl<RI, F, T, R> Transducer<RI> for PartitionByTransducer<F, T, R>
     }
 }
 
+impl<F, T, R> TransducerExt for PartitionByTransducer<F, T, R>
+    where F: Fn(&T) -> R {}
+
 impl<R, I, OF, E, F, X> Reducing<I, OF, E> for PartitionByReducer<R, F, I, X>
     where R: Reducing<Vec<I>, OF, E>,
           F: Fn(&I) -> X,
@@ -705,34 +715,37 @@ impl<R, I, OF, E, F, X> Reducing<I, OF, E> for PartitionByReducer<R, F, I, X>
 
     #[inline]
     fn step(&mut self, value: I) -> Result<StepResult, E> {
-        let last_res = self.last_res.take();
-        match last_res {
-            None => {
-                self.last_res = Some((self.t.f)(&value));
+        let new_res = (self.t.f)(&value);
+        let same = match self.last_res {
+            None => true,
+            Some(ref res) => res == &new_res
+        };
+        if same {
+            self.last_res = Some(new_res);
+            self.holder.push(value);
+            Ok(StepResult::Continue)
+        } else {
+            let mut other_holder = Vec::new();
+            mem::swap(&mut other_holder, &mut self.holder);
+            let result = self.rf.step(other_holder)?;
+            if !result.is_stop() {
+                self.last_res = Some(new_res);
                 self.holder.push(value);
-                Ok(StepResult::Continue)
-            },
-            Some(ref res) => {
-                let new_res = (self.t.f)(&value);
-                if res == &new_res {
-                    self.holder.push(value);
-                    Ok(StepResult::Continue)
-                } else {
-                    self.last_res = Some(new_res);
-                    let mut other_holder = Vec::new();
-                    mem::swap(&mut other_holder, &mut self.holder);
-                    self.holder.push(value);
-                    self.rf.step(other_holder)
-                }
             }
+            Ok(result)
         }
     }
 
-    fn complete(&mut self) -> Result<(), E> {
-        if self.holder.len() > 0 {
+    #[inline]
+    fn accumulated(&mut self) -> Option<&mut OF> {
+        self.rf.accumulated()
+    }
+
+    fn complete(&mut self) -> Result<OF, E> {
+        if !self.holder.is_empty() {
             let mut other_holder = Vec::new();
             mem::swap(&mut other_holder, &mut self.holder);
-            try!(self.rf.step(other_holder));
+            self.rf.step(other_holder)?;
         }
         self.rf.complete()
     }
@@ -747,81 +760,405 @@ pub fn partition_by<F, T, R>(partition_func: F) -> PartitionByTransducer<F, T, R
     }
 }
 
-pub struct InterposeTransducer<T>(T);
+transducer! {
+    #[derive(Clone)]
+    pub struct InterposeTransducer<T> { separator: T }
 
-pub struct InterposeReducer<R, T> {
-    first: bool,
-    rf: R,
-    t: InterposeTransducer<T>
+    pub struct InterposeReducer { first: bool = true }
+
+    impl Reducing for InterposeReducer
+        where T: Clone {
+
+        type Item = T;
+
+        fn step(&mut self, value: T) -> Result<StepResult, E> {
+            if self.first {
+                self.first = false;
+            } else {
+                match self.rf.step(self.t.separator.clone())? {
+                    StepResult::Continue => (),
+                    StepResult::Stop => return Ok(StepResult::Stop)
+                }
+            }
+            self.rf.step(value)
+        }
+    }
+}
+
+pub fn interpose<T>(separator: T) -> InterposeTransducer<T> {
+    InterposeTransducer {
+        separator
+    }
 }
 
-impl<RI, T> Transducer<RI> for InterposeTransducer<T> {
-    type RO = InterposeReducer<RI, T>;
+transducer! {
+    pub struct DedupeTransducer<T> { t: PhantomData<T> }
 
-    fn new(self, reducing_fn: RI) -> Self::RO {
-        InterposeReducer {
-            first: true,
-            rf: reducing_fn,
-            t: self
+    pub struct DedupeReducer { last_val: Option<T> = None }
+
+    impl Reducing for DedupeReducer
+        where T: Eq + Clone {
+
+        type Item = T;
+
+        fn step(&mut self, value: T) -> Result<StepResult, E> {
+            if self.last_val.is_none() {
+                self.last_val = Some(value.clone());
+                self.rf.step(value)
+            } else if self.last_val.as_ref().unwrap() == &value {
+                Ok(StepResult::Continue)
+            } else {
+                self.last_val = Some(value.clone());
+                self.rf.step(value)
+            }
         }
     }
 }
 
-impl<R, I, OF, E> Reducing<I, OF, E> for InterposeReducer<R, I>
-    where I: Clone,
-          R: Reducing<I, OF, E> {
+impl<T> Clone for DedupeTransducer<T> {
+    fn clone(&self) -> Self {
+        DedupeTransducer {
+            t: PhantomData
+        }
+    }
+}
 
-    type Item = I;
+pub fn dedupe<T>() -> DedupeTransducer<T> {
+    DedupeTransducer {
+        t: PhantomData
+    }
+}
 
-    fn init(&mut self) {
-        self.rf.init();
+transducer! {
+    pub struct DistinctTransducer<T> { t: PhantomData<T> }
+
+    pub struct DistinctReducer { seen: HashSet<T> = HashSet::new() }
+
+    impl Reducing for DistinctReducer
+        where T: Eq + Hash + Clone {
+
+        type Item = T;
+
+        fn step(&mut self, value: T) -> Result<StepResult, E> {
+            if self.seen.contains(&value) {
+                Ok(StepResult::Continue)
+            } else {
+                self.seen.insert(value.clone());
+                self.rf.step(value)
+            }
+        }
     }
+}
 
-    #[inline]
-    fn step(&mut self, value: I) -> Result<StepResult, E> {
-        if self.first {
-            self.first = false;
-        } else {
-            match try!(self.rf.step(self.t.0.clone())) {
-                StepResult::Continue => (),
-                StepResult::Stop => return Ok(StepResult::Stop)
+impl<T> Clone for DistinctTransducer<T> {
+    fn clone(&self) -> Self {
+        DistinctTransducer {
+            t: PhantomData
+        }
+    }
+}
+
+/// Removes all duplicates, not just consecutive ones as `dedupe` does.  Every
+/// distinct value is remembered, so memory grows with the number of distinct
+/// values.
+pub fn distinct<T>() -> DistinctTransducer<T> {
+    DistinctTransducer {
+        t: PhantomData
+    }
+}
+
+transducer! {
+    pub struct DistinctByTransducer<F, T, K> { f: F, t: PhantomData<T>, k: PhantomData<K> }
+
+    pub struct DistinctByReducer { seen: HashSet<K> = HashSet::new() }
+
+    impl Reducing for DistinctByReducer
+        where F: Fn(&T) -> K,
+              K: Eq + Hash {
+
+        type Item = T;
+
+        fn step(&mut self, value: T) -> Result<StepResult, E> {
+            if self.seen.insert((self.t.f)(&value)) {
+                self.rf.step(value)
+            } else {
+                Ok(StepResult::Continue)
             }
         }
-        self.rf.step(value)
     }
+}
 
-    fn complete(&mut self) -> Result<(), E> {
-        self.rf.complete()
+impl<F, T, K> Clone for DistinctByTransducer<F, T, K>
+    where F: Clone {
+
+    fn clone(&self) -> Self {
+        DistinctByTransducer {
+            f: self.f.clone(),
+            t: PhantomData,
+            k: PhantomData
+        }
     }
 }
 
-pub fn interpose<T>(separator: T) -> InterposeTransducer<T> {
-    InterposeTransducer(separator)
+/// Removes any value where the result of `key_fn` has been seen before, only
+/// the keys are remembered.
+pub fn distinct_by<F, T, K>(key_fn: F) -> DistinctByTransducer<F, T, K>
+    where F: Fn(&T) -> K {
+
+    DistinctByTransducer {
+        f: key_fn,
+        t: PhantomData,
+        k: PhantomData
+    }
+}
+
+transducer! {
+    pub struct DistinctBoundedTransducer<T> { capacity: usize, t: PhantomData<T> }
+
+    pub struct DistinctBoundedReducer {
+        tick: u64 = 0,
+        seen: HashMap<T, u64> = HashMap::new(),
+        by_tick: BTreeMap<u64, T> = BTreeMap::new()
+    }
+
+    impl Reducing for DistinctBoundedReducer
+        where T: Eq + Hash + Clone {
+
+        type Item = T;
+
+        fn step(&mut self, value: T) -> Result<StepResult, E> {
+            self.tick += 1;
+            if let Some(tick) = self.seen.get_mut(&value) {
+                let key = self.by_tick.remove(tick).unwrap();
+                *tick = self.tick;
+                self.by_tick.insert(self.tick, key);
+                return Ok(StepResult::Continue)
+            }
+            self.seen.insert(value.clone(), self.tick);
+            self.by_tick.insert(self.tick, value.clone());
+            if self.seen.len() > self.t.capacity {
+                let oldest = *self.by_tick.keys().next().unwrap();
+                let key = self.by_tick.remove(&oldest).unwrap();
+                self.seen.remove(&key);
+            }
+            self.rf.step(value)
+        }
+    }
+}
+
+impl<T> Clone for DistinctBoundedTransducer<T> {
+    fn clone(&self) -> Self {
+        DistinctBoundedTransducer {
+            capacity: self.capacity,
+            t: PhantomData
+        }
+    }
+}
+
+/// As `distinct` but remembers at most `capacity` values, when full the least
+/// recently seen is forgotten.  Seeing a value again, even though it is
+/// removed, counts as a use.
+pub fn distinct_bounded<T>(capacity: usize) -> DistinctBoundedTransducer<T> {
+    DistinctBoundedTransducer {
+        capacity,
+        t: PhantomData
+    }
+}
+
+transducer! {
+    pub struct DistinctWithinTransducer<T> { n: usize, t: PhantomData<T> }
+
+    pub struct DistinctWithinReducer {
+        window: VecDeque<T> = VecDeque::new(),
+        counts: HashMap<T, usize> = HashMap::new()
+    }
+
+    impl Reducing for DistinctWithinReducer
+        where T: Eq + Hash + Clone {
+
+        type Item = T;
+
+        fn step(&mut self, value: T) -> Result<StepResult, E> {
+            let seen = self.counts.contains_key(&value);
+            if self.t.n > 0 {
+                if self.window.len() == self.t.n {
+                    let oldest = self.window.pop_front().unwrap();
+                    let remove = {
+                        let count = self.counts.get_mut(&oldest).unwrap();
+                        *count -= 1;
+                        *count == 0
+                    };
+                    if remove {
+                        self.counts.remove(&oldest);
+                    }
+                }
+                *self.counts.entry(value.clone()).or_default() += 1;
+                self.window.push_back(value.clone());
+            }
+            if seen {
+                Ok(StepResult::Continue)
+            } else {
+                self.rf.step(value)
+            }
+        }
+    }
+}
+
+impl<T> Clone for DistinctWithinTransducer<T> {
+    fn clone(&self) -> Self {
+        DistinctWithinTransducer {
+            n: self.n,
+            t: PhantomData
+        }
+    }
+}
+
+/// Removes any value that is equal to one of the previous `n` values, whether
+/// or not those were removed.  `distinct_within(1)` is equivalent to `dedupe`.
+pub fn distinct_within<T>(n: usize) -> DistinctWithinTransducer<T> {
+    DistinctWithinTransducer {
+        n,
+        t: PhantomData
+    }
+}
+
+transducer! {
+    #[derive(Clone)]
+    pub struct RandomSampleTransducer<G> { prob: f64, rng: G }
+
+    pub struct RandomSampleReducer {}
+
+    impl<I> Reducing for RandomSampleReducer
+        where G: Rng {
+
+        type Item = I;
+
+        fn step(&mut self, value: I) -> Result<StepResult, E> {
+            if self.t.rng.next_f64() < self.t.prob {
+                self.rf.step(value)
+            } else {
+                Ok(StepResult::Continue)
+            }
+        }
+    }
 }
 
-pub struct DedupeTransducer<T>(PhantomData<T>);
+/// Retains each value with a probability of `prob`, using a randomly seeded
+/// generator.  Each clone of the transducer, including each application by
+/// reference, is seeded again so samples different positions.
+pub fn random_sample(prob: f64) -> RandomSampleTransducer<Reseeding> {
+    random_sample_with(prob, Reseeding::new())
+}
+
+/// Retains each value with a probability of `prob`, using the given random
+/// number generator.  A cloned transducer starts from the same state as the
+/// original, so a seeded generator will sample the same positions each time.
+pub fn random_sample_with<G>(prob: f64, rng: G) -> RandomSampleTransducer<G>
+    where G: Rng {
+
+    RandomSampleTransducer {
+        prob,
+        rng
+    }
+}
 
-pub struct DedupeReducer<R, T> {
-    last_val: Option<T>,
-    rf: R
+/// Collects the values produced by the functions of a `stateful` transducer,
+/// which are then passed on to the next reducing function in order.
+pub struct Emitter<O> {
+    values: Vec<O>,
+    stop: bool,
+    stopped: bool
 }
 
-impl<RI, T> Transducer<RI> for DedupeTransducer<T> {
-    type RO = DedupeReducer<RI, T>;
+impl<O> Emitter<O> {
+    fn new() -> Self {
+        Emitter {
+            values: Vec::new(),
+            stop: false,
+            stopped: false
+        }
+    }
+
+    /// Produce a value.
+    pub fn emit(&mut self, value: O) {
+        self.values.push(value);
+    }
+
+    /// End the reduction early, once any values already emitted have been
+    /// passed on.
+    pub fn stop(&mut self) {
+        self.stop = true;
+    }
+
+    fn flush<R, OF, E>(&mut self, rf: &mut R) -> Result<StepResult, E>
+        where R: Reducing<O, OF, E> {
+
+        for value in self.values.drain(..) {
+            if rf.step(value)?.is_stop() {
+                self.stopped = true;
+                return Ok(StepResult::Stop)
+            }
+        }
+        if self.stop {
+            Ok(StepResult::Stop)
+        } else {
+            Ok(StepResult::Continue)
+        }
+    }
+}
+
+pub struct StatefulTransducer<S, FS, FC, O> {
+    init: S,
+    step: FS,
+    complete: FC,
+    o: PhantomData<O>
+}
+
+impl<S, FS, FC, O> Clone for StatefulTransducer<S, FS, FC, O>
+    where S: Clone,
+          FS: Clone,
+          FC: Clone {
+
+    fn clone(&self) -> Self {
+        StatefulTransducer {
+            init: self.init.clone(),
+            step: self.step.clone(),
+            complete: self.complete.clone(),
+            o: PhantomData
+        }
+    }
+}
+
+pub struct StatefulReducer<R, S, FS, FC, O> {
+    rf: R,
+    state: S,
+    step: FS,
+    complete: FC,
+    emitter: Emitter<O>
+}
+
+impl<RI, S, FS, FC, O> Transducer<RI> for StatefulTransducer<S, FS, FC, O> {
+    type RO = StatefulReducer<RI, S, FS, FC, O>;
 
     fn new(self, reducing_fn: RI) -> Self::RO {
-        DedupeReducer {
-            last_val: None,
-            rf: reducing_fn
+        StatefulReducer {
+            rf: reducing_fn,
+            state: self.init,
+            step: self.step,
+            complete: self.complete,
+            emitter: Emitter::new()
         }
     }
 }
 
-impl<R, I, OF, E> Reducing<I, OF, E> for DedupeReducer<R, I>
-    where I: Eq + Clone,
-          R: Reducing<I, OF, E> {
+impl<S, FS, FC, O> TransducerExt for StatefulTransducer<S, FS, FC, O> {}
 
-    type Item = I;
+impl<R, S, FS, FC, I, O, OF, E> Reducing<I, OF, E> for StatefulReducer<R, S, FS, FC, O>
+    where FS: Fn(&mut S, I, &mut Emitter<O>),
+          FC: Fn(&mut S, &mut Emitter<O>),
+          R: Reducing<O, OF, E> {
+
+    type Item = O;
 
     fn init(&mut self) {
         self.rf.init();
@@ -829,22 +1166,40 @@ impl<R, I, OF, E> Reducing<I, OF, E> for DedupeReducer<R, I>
 
     #[inline]
     fn step(&mut self, value: I) -> Result<StepResult, E> {
-        if self.last_val.is_none() {
-            self.last_val = Some(value.clone());
-            self.rf.step(value)
-        } else if self.last_val.as_ref().unwrap() == &value {
-            Ok(StepResult::Continue)
-        } else {
-            self.last_val = Some(value.clone());
-            self.rf.step(value)
-        }
+        (self.step)(&mut self.state, value, &mut self.emitter);
+        self.emitter.flush(&mut self.rf)
     }
 
-    fn complete(&mut self) -> Result<(), E> {
+    #[inline]
+    fn accumulated(&mut self) -> Option<&mut OF> {
+        self.rf.accumulated()
+    }
+
+    fn complete(&mut self) -> Result<OF, E> {
+        if !self.emitter.stopped {
+            (self.complete)(&mut self.state, &mut self.emitter);
+            self.emitter.flush(&mut self.rf)?;
+        }
         self.rf.complete()
     }
 }
 
-pub fn dedupe<T>() -> DedupeTransducer<T> {
-    DedupeTransducer(PhantomData)
+/// Build a transducer from a state and two functions, rather than writing
+/// new `Transducer` and `Reducing` implementations.  `step` is called with
+/// the state and each value, and `complete` with the state once the values
+/// have finished, unless the reduction was stopped further down the
+/// pipeline.  Both are given an `Emitter` to produce any number of
+/// values, or to stop the reduction early.
+pub fn stateful<S, I, O, FS, FC>(init_state: S,
+                                 step_fn: FS,
+                                 complete_fn: FC) -> StatefulTransducer<S, FS, FC, O>
+    where FS: Fn(&mut S, I, &mut Emitter<O>),
+          FC: Fn(&mut S, &mut Emitter<O>) {
+
+    StatefulTransducer {
+        init: init_state,
+        step: step_fn,
+        complete: complete_fn,
+        o: PhantomData
+    }
 }